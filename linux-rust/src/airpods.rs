@@ -1,12 +1,17 @@
 use crate::bluetooth::aacp::{AACPManager, ProximityKeyType, AACPEvent};
 use crate::bluetooth::aacp::ControlCommandIdentifiers;
 // use crate::bluetooth::att::ATTManager;
+use crate::bluetooth::managers::DeviceManagers;
 use crate::media_controller::MediaController;
+use crate::ui::messages::BluetoothUIMessage;
 use bluer::Address;
 use log::{debug, info, error};
+use std::collections::HashMap;
 use std::sync::Arc;
 use ksni::Handle;
-use tokio::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration};
 use crate::ui::tray::MyTray;
 
@@ -15,11 +20,39 @@ pub struct AirPodsDevice {
     pub aacp_manager: AACPManager,
     // pub att_manager: ATTManager,
     pub media_controller: Arc<Mutex<MediaController>>,
+    tray_handle: Option<Handle<MyTray>>,
+    /// Every background loop spawned in `new` (command pump, per-control
+    /// subscribers, the main event loop), so `shutdown` can abort all of
+    /// them instead of leaving orphaned tasks running after a disconnect.
+    task_handles: Vec<JoinHandle<()>>,
+    /// BlueZ object path of the adapter this device connected through
+    /// (e.g. `/org/bluez/hci0`), kept around so `shutdown` can unregister
+    /// this device's battery provider.
+    adapter_path: String,
+}
+
+/// Maps `BatteryStatus` to the status code `device_bus`'s
+/// `BatteryLeft`/`BatteryRight`/`BatteryCase` properties expose, since that
+/// service can't depend on `bluetooth::aacp`'s own enum representation over
+/// D-Bus.
+fn battery_status_code(status: crate::bluetooth::aacp::BatteryStatus) -> u8 {
+    use crate::bluetooth::aacp::BatteryStatus;
+    match status {
+        BatteryStatus::NotCharging => 0,
+        BatteryStatus::Charging => 1,
+        BatteryStatus::Disconnected => 2,
+    }
 }
 
 impl AirPodsDevice {
-    pub async fn new(mac_address: Address, tray_handle: Option<Handle<MyTray>>) -> Self {
+    /// `adapter` is the BlueZ adapter these AirPods connected through,
+    /// supplied by the caller (which already has it from resolving
+    /// `--adapter`/enumerating powered adapters) rather than re-opened here,
+    /// so the local MAC and BlueZ object paths below always match the
+    /// adapter BlueZ actually reported the connection on.
+    pub async fn new(mac_address: Address, adapter: bluer::Adapter, tray_handle: Option<Handle<MyTray>>, ui_tx: UnboundedSender<BluetoothUIMessage>) -> Self {
         info!("Creating new AirPodsDevice for {}", mac_address);
+        let mut task_handles = Vec::new();
         let mut aacp_manager = AACPManager::new();
         aacp_manager.connect(mac_address).await;
 
@@ -27,8 +60,15 @@ impl AirPodsDevice {
         // att_manager.connect(mac_address).await.expect("Failed to connect ATT");
 
         if let Some(handle) = &tray_handle {
-            handle.update(|tray: &mut MyTray| tray.connected = true).await;
+            let mac_str = mac_address.to_string();
+            let device_name = crate::devices::config::load().get(&mac_str).map(|entry| entry.display_name().to_string());
+            handle.update(|tray: &mut MyTray| {
+                tray.connected = true;
+                tray.mac_address = Some(mac_str);
+                tray.device_name = device_name;
+            }).await;
         }
+        let tray_handle_for_struct = tray_handle.clone();
 
         info!("Sending handshake");
         if let Err(e) = aacp_manager.send_handshake().await {
@@ -61,9 +101,16 @@ impl AirPodsDevice {
             error!("Failed to request proximity keys: {}", e);
         }
 
-        let session = bluer::Session::new().await.expect("Failed to get bluer session");
-        let adapter = session.default_adapter().await.expect("Failed to get default adapter");
         let local_mac = adapter.address().await.expect("Failed to get adapter address").to_string();
+        let adapter_path = format!("/org/bluez/{}", adapter.name());
+        let device_path = format!("/org/bluez/{}/dev_{}", adapter.name(), mac_address.to_string().replace(':', "_"));
+        {
+            let mac_str = mac_address.to_string();
+            let adapter_path = adapter_path.clone();
+            tokio::task::spawn_blocking(move || {
+                crate::bluetooth::battery_provider::register(&mac_str, &adapter_path, &device_path);
+            });
+        }
 
         let media_controller = Arc::new(Mutex::new(MediaController::new(mac_address.to_string(), local_mac.clone())));
         let mc_clone = media_controller.clone();
@@ -74,15 +121,18 @@ impl AirPodsDevice {
         if let Some(handle) = &tray_handle {
             handle.update(|tray: &mut MyTray| tray.command_tx = Some(command_tx.clone())).await;
         }
+        if crate::settings::load_settings().device_bus_enabled {
+            crate::device_bus::register(&mac_address.to_string(), command_tx.clone());
+        }
 
         let aacp_manager_clone = aacp_manager.clone();
-        tokio::spawn(async move {
+        task_handles.push(tokio::spawn(async move {
             while let Some((id, value)) = command_rx.recv().await {
                 if let Err(e) = aacp_manager_clone.send_control_command(id, &value).await {
                     log::error!("Failed to send control command: {}", e);
                 }
             }
-        });
+        }));
 
         let mc_listener = media_controller.lock().await;
         let aacp_manager_clone_listener = aacp_manager.clone();
@@ -92,20 +142,22 @@ impl AirPodsDevice {
         let (listening_mode_tx, mut listening_mode_rx) = tokio::sync::mpsc::unbounded_channel();
         aacp_manager.subscribe_to_control_command(ControlCommandIdentifiers::ListeningMode, listening_mode_tx).await;
         let tray_handle_clone = tray_handle.clone();
-        tokio::spawn(async move {
+        let mac_str_listening_mode = mac_address.to_string();
+        task_handles.push(tokio::spawn(async move {
             while let Some(value) = listening_mode_rx.recv().await {
                 if let Some(handle) = &tray_handle_clone {
                     handle.update(|tray: &mut MyTray| {
                         tray.listening_mode = Some(value[0]);
                     }).await;
                 }
+                crate::device_bus::update_listening_mode(&mac_str_listening_mode, value[0]);
             }
-        });
+        }));
 
         let (allow_off_tx, mut allow_off_rx) = tokio::sync::mpsc::unbounded_channel();
         aacp_manager.subscribe_to_control_command(ControlCommandIdentifiers::AllowOffOption, allow_off_tx).await;
         let tray_handle_clone = tray_handle.clone();
-        tokio::spawn(async move {
+        task_handles.push(tokio::spawn(async move {
             while let Some(value) = allow_off_rx.recv().await {
                 if let Some(handle) = &tray_handle_clone {
                     handle.update(|tray: &mut MyTray| {
@@ -113,25 +165,28 @@ impl AirPodsDevice {
                     }).await;
                 }
             }
-        });
+        }));
 
         let (conversation_detect_tx, mut conversation_detect_rx) = tokio::sync::mpsc::unbounded_channel();
         aacp_manager.subscribe_to_control_command(ControlCommandIdentifiers::ConversationDetectConfig, conversation_detect_tx).await;
         let tray_handle_clone = tray_handle.clone();
-        tokio::spawn(async move {
+        let mac_str_conversation_detect = mac_address.to_string();
+        task_handles.push(tokio::spawn(async move {
             while let Some(value) = conversation_detect_rx.recv().await {
+                let enabled = value[0] == 0x01;
                 if let Some(handle) = &tray_handle_clone {
                     handle.update(|tray: &mut MyTray| {
-                        tray.conversation_detect_enabled = Some(value[0] == 0x01);
+                        tray.conversation_detect_enabled = Some(enabled);
                     }).await;
                 }
+                crate::device_bus::update_conversation_detect(&mac_str_conversation_detect, enabled);
             }
-        });
+        }));
 
         let (owns_connection_tx, mut owns_connection_rx) = tokio::sync::mpsc::unbounded_channel();
         aacp_manager.subscribe_to_control_command(ControlCommandIdentifiers::OwnsConnection, owns_connection_tx).await;
         let mc_clone_owns = media_controller.clone();
-        tokio::spawn(async move {
+        task_handles.push(tokio::spawn(async move {
             while let Some(value) = owns_connection_rx.recv().await {
                 let owns = value.get(0).copied().unwrap_or(0) != 0;
                 if !owns {
@@ -141,11 +196,13 @@ impl AirPodsDevice {
                     controller.deactivate_a2dp_profile().await;
                 }
             }
-        });
+        }));
 
         let aacp_manager_clone_events = aacp_manager.clone();
         let local_mac_events = local_mac.clone();
-        tokio::spawn(async move {
+        let mac_address_events = mac_address.to_string();
+        let ui_tx_events = ui_tx.clone();
+        task_handles.push(tokio::spawn(async move {
             while let Some(event) = rx.recv().await {
                 match event {
                     AACPEvent::EarDetection(old_status, new_status) => {
@@ -153,9 +210,38 @@ impl AirPodsDevice {
                         let controller = mc_clone.lock().await;
                         debug!("Calling handle_ear_detection with old_status: {:?}, new_status: {:?}", old_status, new_status);
                         controller.handle_ear_detection(old_status, new_status).await;
+
+                        let left_in_ear = new_status.left_in_ear;
+                        let right_in_ear = new_status.right_in_ear;
+                        let _ = ui_tx_events.send(BluetoothUIMessage::EarDetectionChanged(
+                            mac_address_events.clone(),
+                            left_in_ear,
+                            right_in_ear,
+                        ));
+                        if crate::settings::load_settings().ear_detection_auto_pause_enabled {
+                            crate::media::controller_for(&mac_address_events)
+                                .on_ear_status_changed_for(&mac_address_events, left_in_ear, right_in_ear)
+                                .await;
+                        }
                     }
                     AACPEvent::BatteryInfo(battery_info) => {
                         debug!("Received BatteryInfo event: {:?}", battery_info);
+                        for b in &battery_info {
+                            if let Some(part) = crate::bluetooth::battery_provider::BatteryPart::from_component_mask(b.component as u8) {
+                                let mac_str = mac_address_events.clone();
+                                let level = b.level;
+                                tokio::task::spawn_blocking(move || {
+                                    crate::bluetooth::battery_provider::update(&mac_str, part, level);
+                                });
+                            }
+                            let status_code = battery_status_code(b.status);
+                            match b.component as u8 {
+                                0x02 => crate::device_bus::update_battery_right(&mac_address_events, b.level, status_code),
+                                0x04 => crate::device_bus::update_battery_left(&mac_address_events, b.level, status_code),
+                                0x08 => crate::device_bus::update_battery_case(&mac_address_events, b.level, status_code),
+                                _ => {}
+                            }
+                        }
                         if let Some(handle) = &tray_handle {
                             handle.update(|tray: &mut MyTray| {
                                 for b in &battery_info {
@@ -221,13 +307,98 @@ impl AirPodsDevice {
                     _ => {}
                 }
             }
-        });
+        }));
 
         AirPodsDevice {
             mac_address,
             aacp_manager,
             // att_manager,
             media_controller,
+            tray_handle: tray_handle_for_struct,
+            task_handles,
+            adapter_path,
+        }
+    }
+
+    /// Tears down a disconnected AirPods session: aborts every background
+    /// loop spawned in `new` (command pump, per-control subscribers, the
+    /// main event loop), pauses whatever's playing and releases the A2DP
+    /// profile, clears the tray's connected state, and drops this device's
+    /// entry from `device_managers` so a later reconnect builds a fresh
+    /// `AirPodsDevice` instead of layering a second one on top of this one.
+    pub async fn shutdown(&self, device_managers: &Arc<RwLock<HashMap<String, DeviceManagers>>>) {
+        info!("Shutting down AirPods session for {}", self.mac_address);
+        for handle in &self.task_handles {
+            handle.abort();
+        }
+
+        let controller = self.media_controller.lock().await;
+        controller.pause_all_media().await;
+        controller.deactivate_a2dp_profile().await;
+        drop(controller);
+
+        if let Some(handle) = &self.tray_handle {
+            handle.update(|tray: &mut MyTray| {
+                tray.connected = false;
+            }).await;
+        }
+
+        let mac_str = self.mac_address.to_string();
+        let adapter_path = self.adapter_path.clone();
+        tokio::task::spawn_blocking(move || {
+            crate::bluetooth::battery_provider::unregister(&mac_str, &adapter_path);
+        });
+        crate::device_bus::unregister(&self.mac_address.to_string());
+
+        let adapter_name = self.adapter_path.trim_start_matches("/org/bluez/");
+        let key = crate::bluetooth::managers::device_key(adapter_name, &self.mac_address.to_string());
+        device_managers.write().await.remove(&key);
+    }
+
+    /// Called when logind announces the system is about to suspend:
+    /// proactively releases media/A2DP ownership since the kernel is likely
+    /// to drop the AACP L2CAP socket underneath us momentarily anyway.
+    pub async fn prepare_for_sleep(&self) {
+        debug!("{} suspending, releasing media ownership", self.mac_address);
+        let controller = self.media_controller.lock().await;
+        controller.pause_all_media().await;
+        controller.deactivate_a2dp_profile().await;
+    }
+
+    /// Re-runs the handshake/feature-flags/notification-request/proximity-keys
+    /// sequence from `new` after the system resumes from suspend, since the
+    /// kernel typically tears the AACP L2CAP channel down across sleep and
+    /// notifications otherwise silently stop until a manual reconnect.
+    /// Refreshes the tray's connected state to match.
+    pub async fn rearm(&self) {
+        info!("Re-arming AACP session for {} after resume", self.mac_address);
+
+        if let Err(e) = self.aacp_manager.send_handshake().await {
+            error!("Failed to re-send handshake to AirPods device: {}", e);
+        }
+        sleep(Duration::from_millis(100)).await;
+
+        if let Err(e) = self.aacp_manager.send_set_feature_flags_packet().await {
+            error!("Failed to re-set feature flags: {}", e);
+        }
+        sleep(Duration::from_millis(100)).await;
+
+        if let Err(e) = self.aacp_manager.send_notification_request().await {
+            error!("Failed to re-request notifications: {}", e);
+        }
+
+        if let Err(e) = self.aacp_manager.send_proximity_keys_request(
+            vec![ProximityKeyType::Irk, ProximityKeyType::EncKey],
+        ).await {
+            error!("Failed to re-request proximity keys: {}", e);
+        }
+
+        if let Some(handle) = &self.tray_handle {
+            let mac_str = self.mac_address.to_string();
+            handle.update(|tray: &mut MyTray| {
+                tray.connected = true;
+                tray.mac_address = Some(mac_str);
+            }).await;
         }
     }
 }
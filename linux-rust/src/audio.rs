@@ -0,0 +1,99 @@
+use std::sync::OnceLock;
+
+use log::{debug, error, info};
+use tokio::sync::Mutex;
+
+/// Remembers the sink that was default before we took over, so we can put
+/// it back on disconnect instead of leaving the user on a now-missing sink.
+static PREVIOUS_DEFAULT_SINK: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn previous_default_sink() -> &'static Mutex<Option<String>> {
+    PREVIOUS_DEFAULT_SINK.get_or_init(|| Mutex::new(None))
+}
+
+async fn run(cmd: &str, args: &[&str]) -> Option<String> {
+    match tokio::process::Command::new(cmd).args(args).output().await {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        Ok(output) => {
+            debug!("{} {:?} failed: {}", cmd, args, String::from_utf8_lossy(&output.stderr));
+            None
+        }
+        Err(e) => {
+            debug!("Failed to execute {}: {}", cmd, e);
+            None
+        }
+    }
+}
+
+async fn find_sink_for_device(mac: &str) -> Option<String> {
+    // PipeWire/PulseAudio name Bluetooth sinks after the MAC with underscores,
+    // e.g. bluez_output.AA_BB_CC_DD_EE_FF.1
+    let needle = mac.replace(':', "_");
+    let sinks = run("pactl", &["list", "short", "sinks"]).await?;
+    sinks
+        .lines()
+        .find_map(|line| {
+            let name = line.split_whitespace().nth(1)?;
+            if name.contains(&needle) { Some(name.to_string()) } else { None }
+        })
+}
+
+/// A source-output (something recording from a mic) is a reasonable proxy
+/// for "a call/voice app is active", since nothing else in a typical desktop
+/// session captures audio continuously.
+async fn is_voice_call_active() -> bool {
+    run("pactl", &["list", "short", "source-outputs"])
+        .await
+        .is_some_and(|out| !out.trim().is_empty())
+}
+
+/// Switches the default audio sink to the AirPods and selects the A2DP
+/// (playback) or HFP (call/voice) profile, remembering the previous default
+/// sink so it can be restored on disconnect. No-ops unless
+/// `audio_auto_switch_enabled` is set.
+pub async fn on_device_connected(mac: &str) {
+    if !crate::settings::load_settings().audio_auto_switch_enabled {
+        return;
+    }
+
+    let Some(sink) = find_sink_for_device(mac).await else {
+        debug!("No PipeWire/PulseAudio sink found yet for {}, skipping auto-switch", mac);
+        return;
+    };
+    let prefer_hfp = is_voice_call_active().await;
+
+    if let Some(previous) = run("pactl", &["get-default-sink"]).await {
+        if previous != sink {
+            *previous_default_sink().lock().await = Some(previous);
+        }
+    }
+
+    let card = sink.trim_start_matches("bluez_output.").trim_end_matches(".1").to_string();
+    let card_name = format!("bluez_card.{}", card);
+    let profile = if prefer_hfp { "headset-head-unit" } else { "a2dp-sink" };
+    if run("pactl", &["set-card-profile", &card_name, profile]).await.is_none() {
+        debug!("Failed to set profile {} on {}, it may not support it", profile, card_name);
+    }
+
+    if run("pactl", &["set-default-sink", &sink]).await.is_some() {
+        info!("Switched default audio sink to AirPods ({}) with profile {}", sink, profile);
+    } else {
+        error!("Failed to set default sink to {}", sink);
+    }
+}
+
+/// Restores whatever sink was default before we switched to the AirPods.
+pub async fn on_device_disconnected() {
+    if !crate::settings::load_settings().audio_auto_switch_enabled {
+        return;
+    }
+
+    let mut previous = previous_default_sink().lock().await;
+    if let Some(sink) = previous.take() {
+        if run("pactl", &["set-default-sink", &sink]).await.is_some() {
+            info!("Restored previous default audio sink: {}", sink);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::OnceLock;
+
+use bluer::{Adapter, Address, Session};
+use log::debug;
+
+/// The `--adapter` selector `main.rs` resolved at startup, stashed here so
+/// code that isn't handed an `Adapter` directly (the watchdog, the
+/// disconnect-triggered reconnect loop) can re-resolve the same adapter set
+/// instead of guessing at `Session::default_adapter()`.
+static SELECTOR: OnceLock<Option<String>> = OnceLock::new();
+
+/// Records the `--adapter` CLI value. Must be called at most once, before
+/// any call to [`selector`]; `main.rs` does this right after parsing args.
+pub fn set_selector(selector: Option<String>) {
+    let _ = SELECTOR.set(selector);
+}
+
+/// The `--adapter` selector recorded via [`set_selector`], or `None` if it
+/// was never called (e.g. in tests) or the flag was unset.
+pub fn selector() -> Option<&'static str> {
+    SELECTOR.get().and_then(|s| s.as_deref())
+}
+
+/// One HCI controller BlueZ knows about, as enumerated at startup -- mirrors
+/// Floss's `list_hci_devices` so `--adapter` can be pointed at either an
+/// adapter's BlueZ name (`hci0`) or its own Bluetooth address.
+#[derive(Debug, Clone)]
+pub struct AdapterInfo {
+    pub name: String,
+    pub address: Address,
+}
+
+/// Enumerates every HCI adapter BlueZ knows about, regardless of whether
+/// it's currently powered, purely for startup logging.
+pub async fn list(session: &Session) -> bluer::Result<Vec<AdapterInfo>> {
+    let mut adapters = Vec::new();
+    for name in session.adapter_names().await? {
+        let address = session.adapter(&name)?.address().await?;
+        adapters.push(AdapterInfo { name, address });
+    }
+    Ok(adapters)
+}
+
+/// Resolves the `--adapter <hciN|MAC>` flag to the set of adapters LibrePods
+/// should run its LE monitor and BlueZ connection watcher against.
+///
+/// With `selector` set, powers on and returns just that one adapter (by
+/// BlueZ name or address, case-insensitively). With `selector` unset, returns
+/// every currently-powered adapter, falling back to powering on the default
+/// adapter if none are powered yet (e.g. a fresh install with radios off).
+pub async fn resolve(session: &Session, selector: Option<&str>) -> bluer::Result<Vec<Adapter>> {
+    if let Some(selector) = selector {
+        let mut matched = None;
+        for name in session.adapter_names().await? {
+            let adapter = session.adapter(&name)?;
+            let is_match = name.eq_ignore_ascii_case(selector)
+                || adapter.address().await?.to_string().eq_ignore_ascii_case(selector);
+            if is_match {
+                matched = Some(adapter);
+                break;
+            }
+        }
+        let adapter = matched.ok_or_else(|| bluer::Error {
+            kind: bluer::ErrorKind::NotFound,
+            message: format!("No Bluetooth adapter matches '{}'", selector),
+        })?;
+        adapter.set_powered(true).await?;
+        return Ok(vec![adapter]);
+    }
+
+    let mut powered = Vec::new();
+    for name in session.adapter_names().await? {
+        let adapter = session.adapter(&name)?;
+        if adapter.is_powered().await.unwrap_or(false) {
+            powered.push(adapter);
+        }
+    }
+    if !powered.is_empty() {
+        return Ok(powered);
+    }
+
+    debug!("No adapter is powered yet; powering on the default adapter");
+    let adapter = session.default_adapter().await?;
+    adapter.set_powered(true).await?;
+    Ok(vec![adapter])
+}
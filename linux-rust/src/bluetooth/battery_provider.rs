@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use dbus::arg::{RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::channel::Sender;
+use dbus::message::{Message, MatchRule};
+use dbus::Path;
+use log::{debug, error, warn};
+
+const BATTERY_PROVIDER_MANAGER_IFACE: &str = "org.bluez.BatteryProviderManager1";
+const BATTERY_PROVIDER_IFACE: &str = "org.bluez.BatteryProvider1";
+const PROPERTIES_IFACE: &str = "org.freedesktop.DBus.Properties";
+const PROVIDER_SOURCE: &str = "LibrePods";
+
+/// Which AirPods component a battery provider object represents, matching
+/// the `component` byte masks `AACPEvent::BatteryInfo` reports: 0x02 right
+/// bud, 0x04 left bud, 0x08 case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatteryPart {
+    Left,
+    Right,
+    Case,
+}
+
+impl BatteryPart {
+    const ALL: [BatteryPart; 3] = [BatteryPart::Left, BatteryPart::Right, BatteryPart::Case];
+
+    fn path_segment(self) -> &'static str {
+        match self {
+            BatteryPart::Left => "left",
+            BatteryPart::Right => "right",
+            BatteryPart::Case => "case",
+        }
+    }
+
+    /// Maps `AACPEvent::BatteryInfo`'s component byte mask to the part it
+    /// identifies, or `None` for masks this provider doesn't surface.
+    pub fn from_component_mask(mask: u8) -> Option<Self> {
+        match mask {
+            0x04 => Some(BatteryPart::Left),
+            0x02 => Some(BatteryPart::Right),
+            0x08 => Some(BatteryPart::Case),
+            _ => None,
+        }
+    }
+}
+
+struct ProviderObject {
+    percentage: u8,
+    device_path: String,
+}
+
+static OBJECTS: OnceLock<Mutex<HashMap<String, ProviderObject>>> = OnceLock::new();
+
+fn objects() -> &'static Mutex<HashMap<String, ProviderObject>> {
+    OBJECTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+static CONN: OnceLock<Connection> = OnceLock::new();
+
+/// Lazily connects to the system bus and starts a background thread
+/// answering `org.freedesktop.DBus.Properties` calls for whatever provider
+/// objects are currently registered -- mirrors `status_export`'s own
+/// dedicated D-Bus connection, kept separate from `async_main`'s so a
+/// battery-provider method call never has to fight that loop's `process()`
+/// for the connection.
+fn conn() -> Option<&'static Connection> {
+    if let Some(conn) = CONN.get() {
+        return Some(conn);
+    }
+
+    let conn = match Connection::new_system() {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to connect to the system bus for the AirPods battery provider: {}", e);
+            return None;
+        }
+    };
+    conn.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(|msg, conn| {
+            handle_properties_call(&msg, conn);
+            true
+        }),
+    );
+    if CONN.set(conn).is_err() {
+        return CONN.get();
+    }
+
+    std::thread::spawn(|| {
+        let conn = CONN.get().expect("battery provider connection initialized above");
+        loop {
+            if let Err(e) = conn.process(Duration::from_millis(500)) {
+                error!("AirPods battery provider D-Bus connection error: {}", e);
+                break;
+            }
+        }
+    });
+    CONN.get()
+}
+
+fn root_path(mac: &str) -> String {
+    format!("/org/librepods/battery/{}", mac.replace([':', '-'], "_"))
+}
+
+fn object_path(mac: &str, part: BatteryPart) -> String {
+    format!("{}/{}", root_path(mac), part.path_segment())
+}
+
+/// Registers a `org.bluez.BatteryProvider1` object for each AirPods
+/// component (left bud, right bud, case) with BlueZ's
+/// `org.bluez.BatteryProviderManager1`, so GNOME/KDE/UPower's power menus
+/// pick the AirPods' battery levels up as native `org.bluez.Battery1`
+/// devices instead of only showing them in LibrePods' own tray.
+/// `adapter_path`/`device_path` are the BlueZ object paths for the adapter
+/// the AirPods connected through and the device itself (e.g. `/org/bluez/hci0`
+/// and `/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF`). Logs a warning and no-ops if
+/// BlueZ doesn't support `BatteryProviderManager1` -- callers fall back to
+/// the tray-only battery display in that case.
+pub fn register(mac: &str, adapter_path: &str, device_path: &str) {
+    let Some(conn) = conn() else { return };
+
+    let proxy = conn.with_proxy("org.bluez", adapter_path, Duration::from_millis(5000));
+    if let Err(e) = proxy.method_call::<(), _, _, _>(
+        BATTERY_PROVIDER_MANAGER_IFACE,
+        "RegisterBatteryProvider",
+        (Path::from(root_path(mac)),),
+    ) {
+        warn!(
+            "BlueZ doesn't support BatteryProviderManager1 on {} ({}); AirPods battery won't appear in the system power menu",
+            adapter_path, e
+        );
+        return;
+    }
+
+    let mut objects = objects().lock().unwrap();
+    for part in BatteryPart::ALL {
+        objects.insert(object_path(mac, part), ProviderObject { percentage: 0, device_path: device_path.to_string() });
+    }
+    drop(objects);
+    debug!("Registered AirPods battery provider at {} for {}", root_path(mac), device_path);
+}
+
+/// Updates `part`'s `Percentage` and emits `PropertiesChanged` so BlueZ
+/// republishes `org.bluez.Battery1` on the device with the new level. A
+/// no-op if `register` was never called for this MAC, e.g. because BlueZ
+/// doesn't support battery providers.
+pub fn update(mac: &str, part: BatteryPart, percentage: u8) {
+    let Some(conn) = CONN.get() else { return };
+    let path = object_path(mac, part);
+
+    {
+        let mut objects = objects().lock().unwrap();
+        let Some(object) = objects.get_mut(&path) else { return };
+        object.percentage = percentage;
+    }
+
+    let mut changed: HashMap<String, Variant<Box<dyn RefArg>>> = HashMap::new();
+    changed.insert("Percentage".to_string(), Variant(Box::new(percentage) as Box<dyn RefArg>));
+    let Some(signal) = Message::new_signal(path.as_str(), PROPERTIES_IFACE, "PropertiesChanged") else { return };
+    let signal = signal.append3(BATTERY_PROVIDER_IFACE, changed, Vec::<String>::new());
+    let _ = conn.channel().send(signal);
+}
+
+/// Unregisters every battery provider object for `mac`, so a disconnect
+/// teardown doesn't leave BlueZ holding onto a stale battery source until
+/// the next reconnect re-registers it.
+pub fn unregister(mac: &str, adapter_path: &str) {
+    let Some(conn) = CONN.get() else { return };
+
+    let mut objects = objects().lock().unwrap();
+    for part in BatteryPart::ALL {
+        objects.remove(&object_path(mac, part));
+    }
+    drop(objects);
+
+    let proxy = conn.with_proxy("org.bluez", adapter_path, Duration::from_millis(5000));
+    if let Err(e) = proxy.method_call::<(), _, _, _>(
+        BATTERY_PROVIDER_MANAGER_IFACE,
+        "UnregisterBatteryProvider",
+        (Path::from(root_path(mac)),),
+    ) {
+        debug!("Failed to unregister AirPods battery provider for {}: {}", mac, e);
+    }
+}
+
+fn handle_properties_call(msg: &Message, conn: &Connection) {
+    if msg.interface().as_deref() != Some(PROPERTIES_IFACE) {
+        return;
+    }
+    let Some(path) = msg.path() else { return };
+    let path: &str = &path;
+
+    let objects = objects().lock().unwrap();
+    let Some(object) = objects.get(path) else { return };
+    let percentage = object.percentage;
+    let device_path = object.device_path.clone();
+    drop(objects);
+
+    let reply = match msg.member().as_deref() {
+        Some("GetAll") => {
+            let mut props: HashMap<String, Variant<Box<dyn RefArg>>> = HashMap::new();
+            props.insert("Percentage".to_string(), Variant(Box::new(percentage) as Box<dyn RefArg>));
+            props.insert("Device".to_string(), Variant(Box::new(Path::from(device_path)) as Box<dyn RefArg>));
+            props.insert("Source".to_string(), Variant(Box::new(PROVIDER_SOURCE.to_string()) as Box<dyn RefArg>));
+            msg.method_return().append1(props)
+        }
+        Some("Get") => {
+            let Ok((_, property)) = msg.read2::<String, String>() else { return };
+            match property.as_str() {
+                "Percentage" => msg.method_return().append1(Variant(percentage)),
+                "Device" => msg.method_return().append1(Variant(Path::from(device_path))),
+                "Source" => msg.method_return().append1(Variant(PROVIDER_SOURCE.to_string())),
+                _ => return,
+            }
+        }
+        _ => return,
+    };
+    let _ = conn.channel().send(reply);
+}
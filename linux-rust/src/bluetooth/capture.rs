@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+
+use crate::bluetooth::le::{decoder_for, DecodedAdvertisement, Vendor};
+
+fn get_captures_path() -> PathBuf {
+    let data_dir = std::env::var("XDG_DATA_HOME")
+        .unwrap_or_else(|_| format!("{}/.local/share", std::env::var("HOME").unwrap_or_default()));
+    PathBuf::from(data_dir).join("librepods").join("captures.ndjson")
+}
+
+fn get_preferences_path() -> PathBuf {
+    let config_dir = std::env::var("XDG_CONFIG_HOME")
+        .unwrap_or_else(|_| format!("{}/.config", std::env::var("HOME").unwrap_or_default()));
+    PathBuf::from(config_dir).join("librepods").join("preferences.json")
+}
+
+/// Opt-in: set `"capture_advertisements": true` at the top level of
+/// `preferences.json`. Off by default since a capture can contain encrypted
+/// battery/in-ear bytes that a user may not want written to disk implicitly.
+pub fn capture_enabled() -> bool {
+    std::fs::read_to_string(get_preferences_path())
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("capture_advertisements").and_then(|v| v.as_bool()))
+        .unwrap_or(false)
+}
+
+/// One matched manufacturer-data payload, recorded verbatim (still
+/// encrypted, where applicable) so it can be replayed through the decoder
+/// path later without live hardware or a D-Bus/bluer connection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedAdvertisement {
+    pub timestamp_ms: u64,
+    pub mac: String,
+    pub vendor: Vendor,
+    pub mfr_id: u16,
+    pub rssi: Option<i16>,
+    pub payload_hex: String,
+}
+
+/// Appends one capture to the NDJSON log if capturing is enabled. Never
+/// errors the caller -- a failed capture write shouldn't break live decoding.
+pub async fn record(timestamp_ms: u64, mac: &str, vendor: Vendor, mfr_id: u16, rssi: Option<i16>, payload: &[u8]) {
+    if !capture_enabled() {
+        return;
+    }
+
+    let entry = CapturedAdvertisement {
+        timestamp_ms,
+        mac: mac.to_string(),
+        vendor,
+        mfr_id,
+        rssi,
+        payload_hex: hex::encode(payload),
+    };
+    let Ok(mut line) = serde_json::to_string(&entry) else {
+        error!("Failed to serialize captured advertisement for {}", mac);
+        return;
+    };
+    line.push('\n');
+
+    let path = get_captures_path();
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    match tokio::fs::OpenOptions::new().create(true).append(true).open(&path).await {
+        Ok(mut file) => {
+            use tokio::io::AsyncWriteExt;
+            if let Err(e) = file.write_all(line.as_bytes()).await {
+                error!("Failed to write advertisement capture to {}: {}", path.display(), e);
+            } else {
+                debug!("Captured advertisement from {} to {}", mac, path.display());
+            }
+        }
+        Err(e) => error!("Failed to open capture log {}: {}", path.display(), e),
+    }
+}
+
+/// Replays one captured advertisement through the same decoder used live.
+/// `enc_key` must be supplied by the caller (e.g. looked up from
+/// `devices.json` by `entry.mac`) since capture files intentionally don't
+/// embed encryption keys.
+pub fn replay_entry(entry: &CapturedAdvertisement, enc_key: Option<&[u8; 16]>) -> Option<DecodedAdvertisement> {
+    let payload = hex::decode(&entry.payload_hex).ok()?;
+    decoder_for(entry.vendor).decode(entry.mfr_id, &payload, enc_key)
+}
+
+/// Replays every line of a capture file, in order. A line that fails to
+/// parse is skipped (logged) rather than aborting the whole replay, since a
+/// capture attached to a bug report may have been hand-edited or truncated.
+pub fn replay_file(path: &Path, enc_key_for: impl Fn(&str) -> Option<[u8; 16]>) -> Vec<(CapturedAdvertisement, Option<DecodedAdvertisement>)> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        error!("Failed to read capture file {}", path.display());
+        return Vec::new();
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            if line.trim().is_empty() {
+                return None;
+            }
+            match serde_json::from_str::<CapturedAdvertisement>(line) {
+                Ok(entry) => {
+                    let decoded = replay_entry(&entry, enc_key_for(&entry.mac).as_ref());
+                    Some((entry, decoded))
+                }
+                Err(e) => {
+                    error!("Failed to parse capture line: {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
@@ -1,6 +1,6 @@
 use std::cmp::PartialEq;
 use bluer::monitor::{Monitor, MonitorEvent, Pattern};
-use bluer::{Address, Session};
+use bluer::Address;
 use aes::Aes128;
 use aes::cipher::{BlockEncrypt, KeyInit, BlockDecrypt};
 use aes::cipher::generic_array::GenericArray;
@@ -13,7 +13,9 @@ use hex;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::bluetooth::reconnect::{ReconnectManager, Transport};
 use crate::bluetooth::aacp::BatteryStatus;
 use crate::ui::tray::MyTray;
 use crate::bluetooth::aacp::{DeviceData, LEData, DeviceType};
@@ -30,6 +32,208 @@ fn get_preferences_path() -> PathBuf {
     PathBuf::from(config_dir).join("librepods").join("preferences.json")
 }
 
+/// Coarse proximity reading surfaced to the tray, derived from the RSSI
+/// thresholds configured on the advertisement monitor below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProximityState {
+    Near,
+    Far,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ProximityConfig {
+    near_rssi: i16,
+    far_rssi: i16,
+    near_timeout: Duration,
+    far_timeout: Duration,
+}
+
+impl Default for ProximityConfig {
+    fn default() -> Self {
+        Self {
+            near_rssi: -60,
+            far_rssi: -80,
+            near_timeout: Duration::from_secs(2),
+            far_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+fn load_proximity_config() -> ProximityConfig {
+    let Some(contents) = std::fs::read_to_string(get_preferences_path()).ok() else {
+        return ProximityConfig::default();
+    };
+    let Some(value) = serde_json::from_str::<serde_json::Value>(&contents).ok() else {
+        return ProximityConfig::default();
+    };
+    let Some(proximity) = value.get("proximity") else {
+        return ProximityConfig::default();
+    };
+    let default = ProximityConfig::default();
+    ProximityConfig {
+        near_rssi: proximity.get("near_rssi").and_then(|v| v.as_i64()).map(|v| v as i16).unwrap_or(default.near_rssi),
+        far_rssi: proximity.get("far_rssi").and_then(|v| v.as_i64()).map(|v| v as i16).unwrap_or(default.far_rssi),
+        near_timeout: proximity.get("near_timeout_secs").and_then(|v| v.as_u64()).map(Duration::from_secs).unwrap_or(default.near_timeout),
+        far_timeout: proximity.get("far_timeout_secs").and_then(|v| v.as_u64()).map(Duration::from_secs).unwrap_or(default.far_timeout),
+    }
+}
+
+/// A brand we know how to find in an LE advertisement. Adding a new earbud
+/// brand means adding a variant here, a `ScanFilter` in [`scan_filters`], and
+/// a decoder in [`decoder_for`] -- the monitor loop itself never changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Vendor {
+    Apple,
+    Nothing,
+}
+
+/// One entry of a scanfilter sequence, translated into a `Pattern` for the
+/// kernel-side LE advertisement monitor. `content` is matched verbatim at
+/// `start_position` within the AD structure named by `data_type` (`0xFF` is
+/// manufacturer specific data, where `content`'s first two bytes are the
+/// company identifier in little-endian).
+struct ScanFilter {
+    data_type: u8,
+    start_position: u16,
+    content: Vec<u8>,
+    vendor: Vendor,
+}
+
+impl ScanFilter {
+    fn mfr_id(&self) -> Option<u16> {
+        if self.data_type == 0xFF && self.content.len() >= 2 {
+            Some(u16::from_le_bytes([self.content[0], self.content[1]]))
+        } else {
+            None
+        }
+    }
+
+    fn to_pattern(&self) -> Pattern {
+        Pattern {
+            data_type: self.data_type,
+            start_position: self.start_position,
+            content: self.content.clone(),
+        }
+    }
+}
+
+fn scan_filters() -> Vec<ScanFilter> {
+    vec![
+        ScanFilter {
+            data_type: 0xFF,
+            start_position: 0,
+            content: vec![0x4C, 0x00], // Apple manufacturer ID (76) in LE
+            vendor: Vendor::Apple,
+        },
+        ScanFilter {
+            data_type: 0xFF,
+            start_position: 0,
+            // Nothing hasn't published their assigned company ID anywhere we
+            // could find; this is our best guess from a handful of captures
+            // and may need correcting once we see a wider set of devices.
+            content: vec![0x8B, 0x08],
+            vendor: Vendor::Nothing,
+        },
+    ]
+}
+
+/// Battery and in-ear status decoded out of an advertisement, independent of
+/// which vendor produced it. A `None` battery field means either that this
+/// vendor doesn't expose that piece of state in its advertisement, or that
+/// the advertisement reports the component as absent (`0xff`).
+#[derive(Debug, Clone, Default)]
+pub struct DecodedAdvertisement {
+    pub left_battery: Option<(u8, bool)>,  // (percent, charging)
+    pub right_battery: Option<(u8, bool)>,
+    pub case_battery: Option<(u8, bool)>,
+    pub left_in_ear: bool,
+    pub right_in_ear: bool,
+    /// True when the advertisement indicates the earbuds are disconnected
+    /// from their currently bonded host and can be auto-connected to.
+    pub wants_connect: bool,
+}
+
+/// Decodes a matched manufacturer-data payload into vendor-agnostic battery
+/// and in-ear state. Implementations are looked up by [`Vendor`] so the
+/// monitor's hot loop never needs to know a specific brand's byte layout.
+pub(crate) trait AdvertisementDecoder: Send + Sync {
+    fn decode(&self, mfr_id: u16, data: &[u8], enc_key: Option<&[u8; 16]>) -> Option<DecodedAdvertisement>;
+}
+
+struct AppleDecoder;
+
+impl AdvertisementDecoder for AppleDecoder {
+    fn decode(&self, _mfr_id: u16, apple_data: &[u8], enc_key: Option<&[u8; 16]>) -> Option<DecodedAdvertisement> {
+        let enc_key = enc_key?;
+        if apple_data.len() <= 20 {
+            return None;
+        }
+
+        let last_16: [u8; 16] = apple_data[apple_data.len() - 16..].try_into().ok()?;
+        let decrypted = decrypt(enc_key, &last_16);
+        debug!("Decrypted Apple advertisement data: {}", hex::encode(&decrypted));
+
+        let connection_state = apple_data[10] as usize;
+        let wants_connect = connection_state == 0x00;
+
+        let status = apple_data[5] as usize;
+        let primary_left = (status >> 5) & 0x01 == 1;
+        let this_in_case = (status >> 6) & 0x01 == 1;
+        let xor_factor = primary_left ^ this_in_case;
+        let left_in_ear = if xor_factor { (status & 0x02) != 0 } else { (status & 0x08) != 0 };
+        let right_in_ear = if xor_factor { (status & 0x08) != 0 } else { (status & 0x02) != 0 };
+        let is_flipped = !primary_left;
+
+        let left_byte_index = if is_flipped { 2 } else { 1 };
+        let right_byte_index = if is_flipped { 1 } else { 2 };
+
+        let left_byte = decrypted[left_byte_index] as i32;
+        let right_byte = decrypted[right_byte_index] as i32;
+        let case_byte = decrypted[3] as i32;
+
+        let split = |byte: i32| -> Option<(u8, bool)> {
+            if byte == 0xff {
+                None
+            } else {
+                Some(((byte & 0x7F) as u8, (byte & 0x80) != 0))
+            }
+        };
+
+        Some(DecodedAdvertisement {
+            left_battery: split(left_byte),
+            right_battery: split(right_byte),
+            case_battery: split(case_byte),
+            left_in_ear,
+            right_in_ear,
+            wants_connect,
+        })
+    }
+}
+
+struct NothingDecoder;
+
+impl AdvertisementDecoder for NothingDecoder {
+    fn decode(&self, _mfr_id: u16, _data: &[u8], _enc_key: Option<&[u8; 16]>) -> Option<DecodedAdvertisement> {
+        // Nothing earbuds push battery and in-ear state over ATT notifications
+        // once connected (see `devices::nothing`), not in the LE advertisement
+        // itself, so there's nothing to decode here yet. We still match and
+        // route their advertisements so that changes if a firmware revision
+        // starts advertising this state.
+        None
+    }
+}
+
+pub(crate) fn decoder_for(vendor: Vendor) -> &'static dyn AdvertisementDecoder {
+    static APPLE: AppleDecoder = AppleDecoder;
+    static NOTHING: NothingDecoder = NothingDecoder;
+    match vendor {
+        Vendor::Apple => &APPLE,
+        Vendor::Nothing => &NOTHING,
+    }
+}
+
 fn e(key: &[u8; 16], data: &[u8; 16]) -> [u8; 16] {
     let mut swapped_key = *key;
     swapped_key.reverse();
@@ -81,236 +285,255 @@ fn verify_rpa(addr: &str, irk: &[u8; 16]) -> bool {
 impl PartialEq for DeviceType {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (DeviceType::AirPods, DeviceType::AirPods) => true
+            (DeviceType::AirPods, DeviceType::AirPods) => true,
+            (DeviceType::Nothing, DeviceType::Nothing) => true,
+            _ => false,
         }
     }
 }
 
-pub async fn start_le_monitor(tray_handle: Option<ksni::Handle<MyTray>>) -> bluer::Result<()> {
-    let session = Session::new().await?;
-    let adapter = session.default_adapter().await?;
-    adapter.set_powered(true).await?;
+/// Resolves `addr` against our known devices, returning the matching stored
+/// MAC and which vendor it belongs to. Apple uses rotating Private
+/// Resolvable Addresses so it has to be verified against the device's IRK;
+/// Nothing (and presumably future non-Apple vendors) advertise their real,
+/// static address, so a direct lookup is enough.
+fn resolve_device(addr_str: &str, all_devices: &HashMap<String, DeviceData>) -> Option<(String, Vendor)> {
+    for (mac, device_data) in all_devices {
+        match device_data.type_ {
+            DeviceType::AirPods => {
+                let Ok(irk_bytes) = hex::decode(&device_data.le.irk) else { continue };
+                let Ok(irk): Result<[u8; 16], _> = irk_bytes.as_slice().try_into() else { continue };
+                if verify_rpa(addr_str, &irk) {
+                    return Some((mac.clone(), Vendor::Apple));
+                }
+            }
+            DeviceType::Nothing => {
+                if mac == addr_str {
+                    return Some((mac.clone(), Vendor::Nothing));
+                }
+            }
+        }
+    }
+    None
+}
 
+/// Runs the LE advertisement monitor on `adapter`, which the caller must
+/// have already powered on -- callers running multi-adapter support spawn
+/// one of these per adapter being watched, so this no longer opens its own
+/// `Session`/default adapter.
+pub async fn start_le_monitor(adapter: bluer::Adapter, tray_handle: Option<ksni::Handle<MyTray>>) -> bluer::Result<()> {
     let all_devices: HashMap<String, DeviceData> =
         std::fs::read_to_string(get_devices_path())
             .ok()
             .and_then(|s| serde_json::from_str(&s).ok())
             .unwrap_or_default();
 
-    let mut verified_macs: HashMap<Address, String> = HashMap::new();
+    let mut verified_macs: HashMap<Address, (String, Vendor)> = HashMap::new();
     let mut failed_macs: HashSet<Address> = HashSet::new();
-    let connecting_macs = Arc::new(Mutex::new(HashSet::<Address>::new()));
+    let reconnect_manager = ReconnectManager::new(adapter.clone());
+    let proximity_config = load_proximity_config();
 
-    let pattern = Pattern {
-        data_type: 0xFF,  // Manufacturer specific data
-        start_position: 0,
-        content: vec![0x4C, 0x00],  // Apple manufacturer ID (76) in LE
-    };
+    let filters = scan_filters();
+    let vendor_by_mfr_id: HashMap<u16, Vendor> = filters
+        .iter()
+        .filter_map(|f| f.mfr_id().map(|id| (id, f.vendor)))
+        .collect();
+    let patterns: Vec<Pattern> = filters.iter().map(ScanFilter::to_pattern).collect();
 
     let mm = adapter.monitor().await?;
     let mut monitor_handle = mm
         .register(Monitor {
             monitor_type: bluer::monitor::Type::OrPatterns,
-            rssi_low_threshold: None,
-            rssi_high_threshold: None,
-            rssi_low_timeout: None,
-            rssi_high_timeout: None,
-            rssi_sampling_period: None,
-            patterns: Some(vec![pattern]),
+            rssi_low_threshold: Some(proximity_config.far_rssi),
+            rssi_high_threshold: Some(proximity_config.near_rssi),
+            rssi_low_timeout: Some(proximity_config.far_timeout),
+            rssi_high_timeout: Some(proximity_config.near_timeout),
+            rssi_sampling_period: Some(Duration::from_millis(500)),
+            patterns: Some(patterns),
             ..Default::default()
         })
         .await?;
 
-    debug!("Started LE monitor");
+    debug!("Started LE monitor for vendors: {:?}", filters.iter().map(|f| f.vendor).collect::<Vec<_>>());
 
     while let Some(mevt) = monitor_handle.next().await {
-        if let MonitorEvent::DeviceFound(devid) = mevt {
+        match mevt {
+        MonitorEvent::DeviceLost(devid) => {
+            if let Ok(dev) = adapter.device(devid.device) {
+                let addr_str = dev.address().to_string();
+                info!("Lost proximity with {}", addr_str);
+                if let Some(handle) = &tray_handle {
+                    handle.update(|tray: &mut MyTray| {
+                        if tray.mac_address.as_deref() == Some(addr_str.as_str()) {
+                            tray.proximity = Some(ProximityState::Lost);
+                        }
+                    }).await;
+                }
+            }
+        }
+        MonitorEvent::DeviceFound(devid) => {
             let adapter_monitor_clone = adapter.clone();
             let dev = adapter_monitor_clone.device(devid.device)?;
             let addr = dev.address();
             let addr_str = addr.to_string();
 
-            let matched_airpods_mac: Option<String>;
-            let mut matched_enc_key: Option<[u8; 16]> = None;
+            let matched: Option<(String, Vendor)>;
 
-            if let Some(airpods_mac) = verified_macs.get(&addr) {
-                matched_airpods_mac = Some(airpods_mac.clone());
+            if let Some(entry) = verified_macs.get(&addr) {
+                matched = Some(entry.clone());
             } else if failed_macs.contains(&addr) {
                 continue;
             } else {
-                debug!("Checking RPA for device: {}", addr_str);
-                let mut found_mac = None;
-                for (airpods_mac, device_data) in &all_devices {
-                    if device_data.type_ == DeviceType::AirPods {
-                        if let Ok(irk_bytes) = hex::decode(&device_data.le.irk) {
-                            if irk_bytes.len() == 16 {
-                                let irk: [u8; 16] = irk_bytes.as_slice().try_into().unwrap();
-                                debug!("Verifying RPA {} for airpods MAC {} with IRK {}", addr_str, airpods_mac, device_data.le.irk);
-                                if verify_rpa(&addr_str, &irk) {
-                                    info!("Matched our device ({}) with the irk for {}", addr, airpods_mac);
-                                    verified_macs.insert(addr, airpods_mac.clone());
-                                    found_mac = Some(airpods_mac.clone());
-                                    break;
-                                }
-                            }
-                        }
+                debug!("Resolving device: {}", addr_str);
+                match resolve_device(&addr_str, &all_devices) {
+                    Some(entry) => {
+                        info!("Matched our device ({}) to {} ({:?})", addr, entry.0, entry.1);
+                        verified_macs.insert(addr, entry.clone());
+                        matched = Some(entry);
+                    }
+                    None => {
+                        failed_macs.insert(addr);
+                        debug!("Device {} did not match any of our known devices", addr);
+                        continue;
                     }
                 }
+            }
 
-                if let Some(mac) = found_mac {
-                    matched_airpods_mac = Some(mac);
-                } else {
-                    failed_macs.insert(addr);
-                    debug!("Device {} did not match any of our irks", addr);
-                    continue;
-                }
+            let Some((matched_mac, vendor)) = matched else { continue };
+
+            // The kernel-side monitor only emits DeviceFound once RSSI has
+            // crossed rssi_high_threshold, so by definition we're Near.
+            if let Some(handle) = &tray_handle {
+                handle.update(|tray: &mut MyTray| {
+                    if tray.mac_address.as_deref() == Some(matched_mac.as_str()) {
+                        tray.proximity = Some(ProximityState::Near);
+                    }
+                }).await;
             }
 
-            if let Some(ref mac) = matched_airpods_mac {
-                if let Some(device_data) = all_devices.get(mac) {
-                    if !device_data.le.enc_key.is_empty() {
-                        if let Ok(enc_key_bytes) = hex::decode(&device_data.le.enc_key) {
-                            if enc_key_bytes.len() == 16 {
-                                matched_enc_key = Some(enc_key_bytes.as_slice().try_into().unwrap());
-                            }
+            let mut matched_enc_key: Option<[u8; 16]> = None;
+            if let Some(device_data) = all_devices.get(&matched_mac) {
+                if !device_data.le.enc_key.is_empty() {
+                    if let Ok(enc_key_bytes) = hex::decode(&device_data.le.enc_key) {
+                        if enc_key_bytes.len() == 16 {
+                            matched_enc_key = Some(enc_key_bytes.as_slice().try_into().unwrap());
                         }
                     }
                 }
             }
 
-            if matched_airpods_mac.is_some() {
-                let mut events = dev.events().await?;
-                let tray_handle_clone = tray_handle.clone();
-                let connecting_macs_clone = Arc::clone(&connecting_macs);
-                tokio::spawn(async move {
-                    while let Some(ev) = events.next().await {
-                        match ev {
-                            bluer::DeviceEvent::PropertyChanged(prop) => {
-                                match prop {
-                                    bluer::DeviceProperty::ManufacturerData(data) => {
-                                        if let Some(enc_key) = &matched_enc_key {
-                                            if let Some(apple_data) = data.get(&76) {
-                                                if apple_data.len() > 20 {
-                                                    let last_16: [u8; 16] = apple_data[apple_data.len() - 16..].try_into().unwrap();
-                                                    let decrypted = decrypt(enc_key, &last_16);
-                                                    debug!("Decrypted data from airpods_mac {}: {}",
-                                                           matched_airpods_mac.as_ref().unwrap_or(&"unknown".to_string()),
-                                                           hex::encode(&decrypted));
-
-                                                    let connection_state = apple_data[10] as usize;
-                                                    debug!("Connection state: {}", connection_state);
-                                                    if connection_state == 0x00 {
-                                                        let pref_path = get_preferences_path();
-                                                        let preferences: HashMap<String, HashMap<String, bool>> =
-                                                            std::fs::read_to_string(&pref_path)
-                                                                .ok()
-                                                                .and_then(|s| serde_json::from_str(&s).ok())
-                                                                .unwrap_or_default();
-                                                        let auto_connect = preferences.get(matched_airpods_mac.as_ref().unwrap())
-                                                            .and_then(|prefs| prefs.get("autoConnect"))
-                                                            .copied()
-                                                            .unwrap_or(true);
-                                                        debug!("Auto-connect preference for {}: {}", matched_airpods_mac.as_ref().unwrap(), auto_connect);
-                                                        if auto_connect {
-                                                            let real_address = Address::from_str(&addr_str).unwrap();
-                                                            let mut cm = connecting_macs_clone.lock().await;
-                                                            if cm.contains(&real_address) {
-                                                                info!("Already connecting to {}, skipping duplicate attempt.", matched_airpods_mac.as_ref().unwrap());
-                                                                return;
-                                                            }
-                                                            cm.insert(real_address);
-                                                            // let adapter_clone = adapter_monitor_clone.clone();
-                                                            // let real_device = adapter_clone.device(real_address).unwrap();
-                                                            info!("AirPods are disconnected, attempting to connect to {}", matched_airpods_mac.as_ref().unwrap());
-                                                            // if let Err(e) = real_device.connect().await {
-                                                            //     info!("Failed to connect to AirPods {}: {}", matched_airpods_mac.as_ref().unwrap(), e);
-                                                            // } else {
-                                                            //     info!("Successfully connected to AirPods {}", matched_airpods_mac.as_ref().unwrap());
-                                                            // }
-                                                            // call bluetoothctl connect <mac> for now, I don't know why bluer connect isn't working
-                                                            let output = tokio::process::Command::new("bluetoothctl")
-                                                                .arg("connect")
-                                                                .arg(matched_airpods_mac.as_ref().unwrap())
-                                                                .output()
-                                                                .await;
-                                                            match output {
-                                                                Ok(output) => {
-                                                                    if output.status.success() {
-                                                                        info!("Successfully connected to AirPods {}", matched_airpods_mac.as_ref().unwrap());
-                                                                        cm.remove(&real_address);
-                                                                    } else {
-                                                                        let stderr = String::from_utf8_lossy(&output.stderr);
-                                                                        info!("Failed to connect to AirPods {}: {}", matched_airpods_mac.as_ref().unwrap(), stderr);
-                                                                    }
-                                                                }
-                                                                Err(e) => {
-                                                                    info!("Failed to execute bluetoothctl to connect to AirPods {}: {}", matched_airpods_mac.as_ref().unwrap(), e);
-                                                                }
-                                                            }
-                                                            info!("Auto-connect is disabled for {}, not attempting to connect.", matched_airpods_mac.as_ref().unwrap());
-                                                        }
-                                                    }
-
-                                                    let status = apple_data[5] as usize;
-                                                    let primary_left = (status >> 5) & 0x01 == 1;
-                                                    let this_in_case = (status >> 6) & 0x01 == 1;
-                                                    let xor_factor = primary_left ^ this_in_case;
-                                                    let is_left_in_ear = if xor_factor { (status & 0x02) != 0 } else { (status & 0x08) != 0 };
-                                                    let is_right_in_ear = if xor_factor { (status & 0x08) != 0 } else { (status & 0x02) != 0 };
-                                                    let is_flipped = !primary_left;
-                                                    
-                                                    let left_byte_index = if is_flipped { 2 } else { 1 };
-                                                    let right_byte_index = if is_flipped { 1 } else { 2 };
-                                                    
-                                                    let left_byte = decrypted[left_byte_index] as i32;
-                                                    let right_byte = decrypted[right_byte_index] as i32;
-                                                    let case_byte = decrypted[3] as i32;
-                                                    
-                                                    let (left_battery, left_charging) = if left_byte == 0xff {
-                                                        (0, false)
-                                                    } else {
-                                                        (left_byte & 0x7F, (left_byte & 0x80) != 0)
-                                                    };
-                                                    let (right_battery, right_charging) = if right_byte == 0xff {
-                                                        (0, false)
-                                                    } else {
-                                                        (right_byte & 0x7F, (right_byte & 0x80) != 0)
-                                                    };
-                                                    let (case_battery, case_charging) = if case_byte == 0xff {
-                                                        (0, false)
-                                                    } else {
-                                                        (case_byte & 0x7F, (case_byte & 0x80) != 0)
-                                                    };
-                                                    
-                                                    if let Some(handle) = &tray_handle_clone {
-                                                        handle.update(|tray: &mut MyTray| {
-                                                            tray.battery_l = if left_byte == 0xff { None } else { Some(left_battery as u8) };
-                                                            tray.battery_l_status = if left_byte == 0xff { Some(BatteryStatus::Disconnected) } else if left_charging { Some(BatteryStatus::Charging) } else { Some(BatteryStatus::NotCharging) };
-                                                            tray.battery_r = if right_byte == 0xff { None } else { Some(right_battery as u8) };
-                                                            tray.battery_r_status = if right_byte == 0xff { Some(BatteryStatus::Disconnected) } else if right_charging { Some(BatteryStatus::Charging) } else { Some(BatteryStatus::NotCharging) };
-                                                            tray.battery_c = if case_byte == 0xff { None } else { Some(case_battery as u8) };
-                                                            tray.battery_c_status = if case_byte == 0xff { Some(BatteryStatus::Disconnected) } else if case_charging { Some(BatteryStatus::Charging) } else { Some(BatteryStatus::NotCharging) };
-                                                        }).await;
-                                                    }
-                                                    
-                                                    debug!("Battery status: Left: {}, Right: {}, Case: {}, InEar: L:{} R:{}",
-                                                          if left_byte == 0xff { "disconnected".to_string() } else { format!("{}% (charging: {})", left_battery, left_charging) },
-                                                          if right_byte == 0xff { "disconnected".to_string() } else { format!("{}% (charging: {})", right_battery, right_charging) },
-                                                          if case_byte == 0xff { "disconnected".to_string() } else { format!("{}% (charging: {})", case_battery, case_charging) },
-                                                          is_left_in_ear, is_right_in_ear);
-                                                }
-                                            }
-                                        }
+            let dev_for_capture = dev.clone();
+            let mut events = dev.events().await?;
+            let tray_handle_clone = tray_handle.clone();
+            let reconnect_manager = Arc::clone(&reconnect_manager);
+            let vendor_by_mfr_id = vendor_by_mfr_id.clone();
+            let tray_handle_rssi = tray_handle.clone();
+            let matched_mac_rssi = matched_mac.clone();
+            tokio::spawn(async move {
+                while let Some(ev) = events.next().await {
+                    let bluer::DeviceEvent::PropertyChanged(prop) = ev else { continue };
+
+                    if let bluer::DeviceProperty::Rssi(rssi) = prop {
+                        // Between the low and high thresholds the kernel-side
+                        // monitor stays quiet, so we classify "Far" ourselves
+                        // from the raw RSSI property instead.
+                        if rssi < proximity_config.near_rssi && rssi >= proximity_config.far_rssi {
+                            if let Some(handle) = &tray_handle_rssi {
+                                handle.update(|tray: &mut MyTray| {
+                                    if tray.mac_address.as_deref() == Some(matched_mac_rssi.as_str()) {
+                                        tray.proximity = Some(ProximityState::Far);
                                     }
-                                    _ => {}
-                                }
+                                }).await;
                             }
                         }
+                        continue;
                     }
-                });
-            }
+
+                    let bluer::DeviceProperty::ManufacturerData(data) = prop else {
+                        continue;
+                    };
+
+                    let Some(mfr_id) = vendor_by_mfr_id.iter().find_map(|(id, v)| (*v == vendor).then_some(*id)) else {
+                        continue;
+                    };
+                    let Some(payload) = data.get(&mfr_id) else { continue };
+
+                    if crate::bluetooth::capture::capture_enabled() {
+                        let timestamp_ms = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_millis() as u64)
+                            .unwrap_or(0);
+                        let rssi = dev_for_capture.rssi().await.ok().flatten();
+                        crate::bluetooth::capture::record(timestamp_ms, &matched_mac, vendor, mfr_id, rssi, payload).await;
+                    }
+
+                    let Some(decoded) = decoder_for(vendor).decode(mfr_id, payload, matched_enc_key.as_ref()) else {
+                        continue;
+                    };
+
+                    debug!(
+                        "Decoded advertisement for {} ({:?}): L:{:?} R:{:?} C:{:?} InEar L:{} R:{}",
+                        matched_mac, vendor, decoded.left_battery, decoded.right_battery, decoded.case_battery,
+                        decoded.left_in_ear, decoded.right_in_ear
+                    );
+
+                    if crate::settings::load_settings().ear_detection_auto_pause_enabled {
+                        crate::media::controller_for(&matched_mac)
+                            .on_ear_status_changed_for(&matched_mac, decoded.left_in_ear, decoded.right_in_ear)
+                            .await;
+                    }
+
+                    if decoded.wants_connect {
+                        // Gated on the same `devices.json` `auto_reconnect` flag as
+                        // `reconnect::spawn_auto_reconnect` and the watchdog, rather
+                        // than a separate `preferences.json` key, so there's one
+                        // opt-in switch for "should this device reconnect itself"
+                        // no matter which trigger noticed it was gone.
+                        let auto_connect = crate::bluetooth::reconnect::is_auto_reconnect_enabled(&matched_mac);
+                        debug!("Auto-reconnect enabled for {}: {}", matched_mac, auto_connect);
+                        if auto_connect {
+                            let Ok(real_address) = Address::from_str(&addr_str) else { continue };
+                            let transport = match vendor {
+                                // AirPods report battery over LE but take audio over BR/EDR.
+                                Vendor::Apple => Transport::BrEdr,
+                                Vendor::Nothing => Transport::BrEdr,
+                            };
+                            info!("{} is disconnected, attempting to connect to {}", vendor_label(vendor), matched_mac);
+                            reconnect_manager.request_connect(real_address, matched_mac.clone(), transport).await;
+                        }
+                    }
+
+                    if let Some(handle) = &tray_handle_clone {
+                        handle.update(|tray: &mut MyTray| {
+                            tray.battery_l = decoded.left_battery.map(|(pct, _)| pct);
+                            tray.battery_l_status = Some(battery_status(decoded.left_battery));
+                            tray.battery_r = decoded.right_battery.map(|(pct, _)| pct);
+                            tray.battery_r_status = Some(battery_status(decoded.right_battery));
+                            tray.battery_c = decoded.case_battery.map(|(pct, _)| pct);
+                            tray.battery_c_status = Some(battery_status(decoded.case_battery));
+                        }).await;
+                    }
+                }
+            });
+        }
+        _ => {}
         }
     }
 
     Ok(())
 }
+
+fn vendor_label(vendor: Vendor) -> &'static str {
+    match vendor {
+        Vendor::Apple => "AirPods",
+        Vendor::Nothing => "Nothing earbuds",
+    }
+}
+
+fn battery_status(battery: Option<(u8, bool)>) -> BatteryStatus {
+    match battery {
+        None => BatteryStatus::Disconnected,
+        Some((_, true)) => BatteryStatus::Charging,
+        Some((_, false)) => BatteryStatus::NotCharging,
+    }
+}
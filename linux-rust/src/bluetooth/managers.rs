@@ -13,18 +13,52 @@ pub struct DeviceManagers {
     aacp: Option<Arc<AACPManager>>,
 }
 
+/// Key a device is tracked under in `device_managers`/`active_airpods`:
+/// the same earbuds can be paired to more than one adapter (e.g. a laptop's
+/// built-in radio and a USB dongle), so the MAC alone isn't unique enough --
+/// it's qualified by which adapter the device is currently connected
+/// through.
+pub fn device_key(adapter_name: &str, mac: &str) -> String {
+    format!("{}/{}", adapter_name, mac)
+}
+
+/// Finds the `device_key(adapter, mac)`-qualified key for `mac` in a
+/// `device_managers`-shaped map, for callers (the UI layer) that only ever
+/// learn a bare MAC from a `BluetoothUIMessage` and don't know which adapter
+/// reported it. Assumes a given MAC is tracked under at most one adapter at
+/// a time, which holds for every caller today.
+pub fn key_for_mac<'a, V>(map: &'a HashMap<String, V>, mac: &str) -> Option<&'a String> {
+    map.keys().find(|key| key.ends_with(&format!("/{}", mac)))
+}
+
 impl DeviceManagers {
     fn new() -> Self {
         Self { att: None, aacp: None }
     }
 
-    fn with_aacp(aacp: AACPManager) -> Self {
+    pub fn with_aacp(aacp: AACPManager) -> Self {
         Self { att: None, aacp: Some(Arc::new(aacp)) }
     }
 
-    fn with_att(att: ATTManager) -> Self {
+    pub fn with_att(att: ATTManager) -> Self {
         Self { att: Some(Arc::new(att)), aacp: None }
     }
+
+    pub fn set_aacp(&mut self, aacp: AACPManager) {
+        self.aacp = Some(Arc::new(aacp));
+    }
+
+    pub fn set_att(&mut self, att: ATTManager) {
+        self.att = Some(Arc::new(att));
+    }
+
+    pub fn get_aacp(&self) -> Option<Arc<AACPManager>> {
+        self.aacp.clone()
+    }
+
+    pub fn get_att(&self) -> Option<Arc<ATTManager>> {
+        self.att.clone()
+    }
 }
 
 pub struct BluetoothDevices {
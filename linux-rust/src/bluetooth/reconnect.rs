@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use bluer::{Adapter, Address};
+use log::{debug, info, warn};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
+
+use crate::bluetooth::adapters;
+use crate::devices::config;
+use crate::ui::messages::BluetoothUIMessage;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Backoff ladder for [`spawn_auto_reconnect`]: 1s, 2s, 4s, ... capped at 30s.
+/// Deliberately separate from `BASE_BACKOFF`/`MAX_BACKOFF` above, which back
+/// off LE-scan-triggered connect attempts rather than a disconnect-driven
+/// retry loop.
+const DISCONNECT_BASE_BACKOFF: Duration = Duration::from_secs(1);
+const DISCONNECT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// MACs with an auto-reconnect loop currently running, so a flappy device
+/// that disconnects repeatedly doesn't pile up duplicate loops.
+static ACTIVE_AUTO_RECONNECTS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+fn active_auto_reconnects() -> &'static Mutex<HashSet<String>> {
+    ACTIVE_AUTO_RECONNECTS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Looks up whether `mac` is still known and opted into auto-reconnect.
+/// Re-reads the devices file every call (rather than trusting a snapshot)
+/// so a user removing the device cancels the loop. This is the single
+/// opt-in flag every reconnect trigger in this module (and `bluetooth::le`'s
+/// advertisement-triggered path) is gated on -- `devices.json`'s
+/// `auto_reconnect`, the same field the "Auto-reconnect when dropped"
+/// toggle in the UI writes.
+pub(crate) fn is_auto_reconnect_enabled(mac: &str) -> bool {
+    config::load().get(mac).is_some_and(|entry| entry.data.auto_reconnect)
+}
+
+/// Spawns a background task that repeatedly tries to reconnect `mac` after
+/// an unexpected disconnect, backing off 1s, 2s, 4s, ... up to 30s between
+/// attempts. Re-fetches the adapter and re-discovers the device on every
+/// attempt, so it survives the adapter itself disappearing (e.g. Bluetooth
+/// toggled off) and coming back. Reports each attempt back via `ui_tx` as a
+/// `ReconnectionStatusChanged(mac, true/false)` pair so panes can render a
+/// "Reconnecting..." state, then a final `DeviceConnected` once it succeeds.
+/// Stops once the device reconnects, the user removes the device from
+/// `devices.json`, or another auto-reconnect loop for the same MAC is
+/// already running.
+pub fn spawn_auto_reconnect(mac: String, ui_tx: UnboundedSender<BluetoothUIMessage>) {
+    tokio::spawn(async move {
+        {
+            let mut active = active_auto_reconnects().lock().await;
+            if !active.insert(mac.clone()) {
+                debug!("Auto-reconnect already running for {}, not starting another.", mac);
+                return;
+            }
+        }
+
+        let result = auto_reconnect_loop(&mac, &ui_tx).await;
+
+        active_auto_reconnects().lock().await.remove(&mac);
+        let _ = ui_tx.send(BluetoothUIMessage::ReconnectionStatusChanged(mac.clone(), false));
+
+        match result {
+            AutoReconnectOutcome::Reconnected => {
+                info!("Auto-reconnect succeeded for {}", mac);
+                let _ = ui_tx.send(BluetoothUIMessage::DeviceConnected(mac));
+            }
+            AutoReconnectOutcome::Cancelled => {
+                debug!("Auto-reconnect for {} cancelled (device removed or no longer opted in).", mac);
+            }
+        }
+    });
+}
+
+enum AutoReconnectOutcome {
+    Reconnected,
+    Cancelled,
+}
+
+async fn auto_reconnect_loop(mac: &str, ui_tx: &UnboundedSender<BluetoothUIMessage>) -> AutoReconnectOutcome {
+    let Ok(addr) = mac.parse::<Address>() else {
+        warn!("Auto-reconnect: {} is not a valid Bluetooth address, giving up.", mac);
+        return AutoReconnectOutcome::Cancelled;
+    };
+
+    let mut attempt: u32 = 0;
+    loop {
+        if !is_auto_reconnect_enabled(mac) {
+            return AutoReconnectOutcome::Cancelled;
+        }
+
+        let _ = ui_tx.send(BluetoothUIMessage::ReconnectionStatusChanged(mac.to_string(), true));
+        match try_reconnect_once(addr).await {
+            Ok(()) => return AutoReconnectOutcome::Reconnected,
+            Err(e) => {
+                let backoff = DISCONNECT_BASE_BACKOFF.saturating_mul(2u32.saturating_pow(attempt)).min(DISCONNECT_MAX_BACKOFF);
+                debug!("Auto-reconnect attempt for {} failed ({}), retrying in {:?}", mac, e, backoff);
+                attempt += 1;
+                let _ = ui_tx.send(BluetoothUIMessage::ReconnectionStatusChanged(mac.to_string(), false));
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+}
+
+/// Fetches a fresh session/adapter/device handle rather than reusing a
+/// cached one, so a missing or just-re-enabled adapter is picked up the
+/// next time this is called instead of erroring forever. Resolves against
+/// the same `--adapter` selector `main.rs` handed to the LE monitor (via
+/// [`adapters::selector`]) rather than BlueZ's notion of a "default"
+/// adapter, so a device monitored on a non-default adapter (e.g.
+/// `--adapter hci1`) is still found here.
+async fn try_reconnect_once(addr: Address) -> Result<(), String> {
+    let session = bluer::Session::new().await.map_err(|e| format!("no bluer session: {}", e))?;
+    let candidates = adapters::resolve(&session, adapters::selector())
+        .await
+        .map_err(|e| format!("no adapter: {}", e))?;
+
+    let mut last_err = "no powered adapter knows this device".to_string();
+    for adapter in candidates {
+        if !adapter.is_powered().await.unwrap_or(false) {
+            continue;
+        }
+        let Ok(device) = adapter.device(addr) else { continue };
+        match tokio::time::timeout(CONNECT_TIMEOUT, device.connect()).await {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => last_err = e.to_string(),
+            Err(_) => last_err = "connect timed out".to_string(),
+        }
+    }
+    Err(last_err)
+}
+
+/// Mirrors Android's `BluetoothDevice` transport hint: which radio to prefer
+/// when a device advertises over both BR/EDR and LE (as AirPods do).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    BrEdr,
+    Le,
+}
+
+#[derive(Debug, Clone)]
+enum ConnState {
+    Connecting,
+    Connected,
+    Backoff { attempt: u32, until: Instant },
+}
+
+/// Tracks per-MAC connection attempts so a failed connect backs off instead
+/// of either leaking a stuck "connecting" flag or hammering the adapter.
+pub struct ReconnectManager {
+    adapter: Adapter,
+    state: Mutex<HashMap<Address, ConnState>>,
+}
+
+impl ReconnectManager {
+    pub fn new(adapter: Adapter) -> Arc<Self> {
+        Arc::new(Self { adapter, state: Mutex::new(HashMap::new()) })
+    }
+
+    /// Requests a connection to `addr`. No-ops if we're already connecting,
+    /// already connected, or still within this MAC's backoff window.
+    pub async fn request_connect(self: &Arc<Self>, addr: Address, mac_label: String, transport: Transport) {
+        {
+            let mut state = self.state.lock().await;
+            match state.get(&addr) {
+                Some(ConnState::Connecting) => {
+                    debug!("Already connecting to {}, skipping duplicate request.", mac_label);
+                    return;
+                }
+                Some(ConnState::Connected) => {
+                    debug!("{} is already connected.", mac_label);
+                    return;
+                }
+                Some(ConnState::Backoff { until, .. }) if *until > Instant::now() => {
+                    debug!("{} is in backoff, skipping connect attempt.", mac_label);
+                    return;
+                }
+                _ => {}
+            }
+            state.insert(addr, ConnState::Connecting);
+        }
+
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            this.attempt_connect(addr, mac_label, transport).await;
+        });
+    }
+
+    async fn attempt_connect(self: Arc<Self>, addr: Address, mac_label: String, transport: Transport) {
+        // Whatever happens below, never leave this MAC stuck in `Connecting`.
+        let outcome = self.try_connect(addr, &mac_label, transport).await;
+
+        let mut state = self.state.lock().await;
+        match outcome {
+            Ok(()) => {
+                info!("Successfully connected to {}", mac_label);
+                state.insert(addr, ConnState::Connected);
+            }
+            Err(e) => {
+                let attempt = match state.get(&addr) {
+                    Some(ConnState::Backoff { attempt, .. }) => attempt + 1,
+                    _ => 1,
+                };
+                let backoff = (BASE_BACKOFF * 2u32.pow(attempt.saturating_sub(1))).min(MAX_BACKOFF);
+                warn!("Failed to connect to {}: {} (retrying in {:?})", mac_label, e, backoff);
+                state.insert(addr, ConnState::Backoff { attempt, until: Instant::now() + backoff });
+
+                let this = Arc::clone(&self);
+                tokio::spawn(async move {
+                    tokio::time::sleep(backoff).await;
+                    this.request_connect(addr, mac_label, transport).await;
+                });
+            }
+        }
+    }
+
+    async fn try_connect(&self, addr: Address, mac_label: &str, transport: Transport) -> Result<(), String> {
+        let device = self.adapter.device(addr).map_err(|e| e.to_string())?;
+
+        let native_result = tokio::time::timeout(CONNECT_TIMEOUT, device.connect()).await;
+        match native_result {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => {
+                debug!("Native bluer connect to {} failed ({}), falling back to bluetoothctl", mac_label, e);
+            }
+            Err(_) => {
+                debug!("Native bluer connect to {} timed out after {:?}, falling back to bluetoothctl", mac_label, CONNECT_TIMEOUT);
+            }
+        }
+
+        // bluer's Device::connect doesn't let us pin a specific transport the
+        // way Android's BtTransport does; bluetoothctl goes through BlueZ's
+        // own transport selection, which is the best we can do here.
+        let _ = transport;
+        let output = tokio::time::timeout(
+            CONNECT_TIMEOUT,
+            tokio::process::Command::new("bluetoothctl").arg("connect").arg(mac_label).output(),
+        )
+        .await
+        .map_err(|_| "bluetoothctl timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
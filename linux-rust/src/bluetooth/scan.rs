@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use bluer::agent::{Agent, AgentHandle, ReqError, RequestPasskey};
+use bluer::{Adapter, AdapterEvent, Address, Session};
+use futures::StreamExt;
+use log::{debug, info, warn};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{oneshot, Mutex};
+
+use crate::devices::enums::DeviceType;
+
+/// One live result surfaced to the Add-Device pane while a discovery is in
+/// progress. Kept separate from `devices::enums::DeviceData` -- this is
+/// transient scan state, not the persisted record a device gets once added.
+#[derive(Debug, Clone)]
+pub struct DiscoveredDevice {
+    pub address: Address,
+    pub name: String,
+    pub rssi: Option<i16>,
+    pub bonded: bool,
+    pub connected: bool,
+    /// Best-effort guess at `DeviceType` from the advertised manufacturer
+    /// data, so the Add-Device flow can pre-fill `selected_device_type`
+    /// instead of making the user pick from a one-item combo box.
+    pub detected_type: Option<DeviceType>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    DeviceUpdated(DiscoveredDevice),
+    DeviceRemoved(Address),
+    PasskeyRequested { address: Address },
+    PairingSucceeded(Address),
+    PairingFailed(Address, String),
+}
+
+/// Maps a vendor's manufacturer-ID to the `DeviceType` we'd create for it,
+/// mirroring the `Vendor` -> brand mapping `bluetooth::le` uses for the
+/// passive advertisement monitor.
+fn detect_device_type(manufacturer_data: &HashMap<u16, Vec<u8>>) -> Option<DeviceType> {
+    for mfr_id in manufacturer_data.keys() {
+        match mfr_id {
+            0x004C => return Some(DeviceType::AirPods), // Apple
+            0x088B => return Some(DeviceType::Nothing),
+            _ => {}
+        }
+    }
+    None
+}
+
+async fn snapshot_device(adapter: &Adapter, addr: Address) -> Option<DiscoveredDevice> {
+    let device = adapter.device(addr).ok()?;
+    let name = device.name().await.ok().flatten().unwrap_or_else(|| addr.to_string());
+    let rssi = device.rssi().await.ok().flatten();
+    let bonded = device.is_paired().await.unwrap_or(false);
+    let connected = device.is_connected().await.unwrap_or(false);
+    let manufacturer_data = device.manufacturer_data().await.ok().flatten().unwrap_or_default();
+    let detected_type = detect_device_type(&manufacturer_data);
+
+    Some(DiscoveredDevice {
+        address: addr,
+        name,
+        rssi,
+        bonded,
+        connected,
+        detected_type,
+    })
+}
+
+/// Drives a `bluer` discovery session and streams every discovered/updated
+/// device back through `tx`. Intended to be driven from its own task and
+/// cancelled by aborting that task -- there's no graceful stop on the
+/// `bluer` side beyond dropping the discovery stream.
+pub async fn run_discovery(adapter: Adapter, tx: UnboundedSender<ScanEvent>) -> bluer::Result<()> {
+    let mut events = adapter.discover_devices().await?;
+    info!("Started Bluetooth discovery for Add Device");
+
+    while let Some(event) = events.next().await {
+        match event {
+            AdapterEvent::DeviceAdded(addr) => {
+                if let Some(found) = snapshot_device(&adapter, addr).await {
+                    debug!("Discovered device {} ({})", found.name, found.address);
+                    if tx.send(ScanEvent::DeviceUpdated(found)).is_err() {
+                        break;
+                    }
+                }
+            }
+            AdapterEvent::DeviceRemoved(addr) => {
+                let _ = tx.send(ScanEvent::DeviceRemoved(addr));
+            }
+            AdapterEvent::PropertyChanged(_) => {}
+        }
+    }
+
+    info!("Bluetooth discovery stopped");
+    Ok(())
+}
+
+/// Holds the passkey the UI typed into the dialog raised for the most
+/// recent `ScanEvent::PasskeyRequested`. `register_pairing_agent`'s
+/// `request_passkey` callback blocks on the receiving half; `submit_passkey`
+/// is called from the UI thread once the user confirms the dialog.
+static PENDING_PASSKEY: OnceLock<Mutex<Option<oneshot::Sender<u32>>>> = OnceLock::new();
+
+fn pending_passkey() -> &'static Mutex<Option<oneshot::Sender<u32>>> {
+    PENDING_PASSKEY.get_or_init(|| Mutex::new(None))
+}
+
+/// Feeds the passkey entered in the UI's pairing dialog back to whichever
+/// `request_passkey` call is currently waiting on it. A no-op if nothing is
+/// waiting (e.g. the dialog was left open after the pairing attempt timed
+/// out).
+pub async fn submit_passkey(passkey: u32) {
+    if let Some(sender) = pending_passkey().lock().await.take() {
+        let _ = sender.send(passkey);
+    }
+}
+
+/// Registers a pairing agent whose passkey prompt is forwarded to `tx` as
+/// `ScanEvent::PasskeyRequested` rather than rejected or auto-confirmed, so
+/// the Add-Device pane can show a real entry dialog for devices that need
+/// one.
+async fn register_pairing_agent(session: &Session, tx: UnboundedSender<ScanEvent>) -> bluer::Result<AgentHandle> {
+    let agent = Agent {
+        request_default: true,
+        request_passkey: Some(Box::new(move |req: RequestPasskey| {
+            let tx = tx.clone();
+            Box::pin(async move {
+                let (passkey_tx, passkey_rx) = oneshot::channel();
+                *pending_passkey().lock().await = Some(passkey_tx);
+                if tx
+                    .send(ScanEvent::PasskeyRequested { address: req.device })
+                    .is_err()
+                {
+                    return Err(ReqError::Canceled);
+                }
+                passkey_rx.await.map_err(|_| ReqError::Canceled)
+            })
+        })),
+        ..Default::default()
+    };
+    session.register_agent(agent).await
+}
+
+/// Bonds `addr`, registering a pairing agent so a passkey request from the
+/// adapter is surfaced back through `tx` instead of failing the pairing
+/// outright. Mirrors the reconnect subsystem's pattern of reporting
+/// outcomes back over the same channel the UI already reads.
+pub async fn pair_device(session: Session, adapter: Adapter, addr: Address, tx: UnboundedSender<ScanEvent>) {
+    let _agent_handle = match register_pairing_agent(&session, tx.clone()).await {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            warn!("Failed to register pairing agent, continuing with adapter's default: {}", e);
+            None
+        }
+    };
+
+    let device = match adapter.device(addr) {
+        Ok(device) => device,
+        Err(e) => {
+            warn!("Cannot look up {} to pair: {}", addr, e);
+            let _ = tx.send(ScanEvent::PairingFailed(addr, e.to_string()));
+            return;
+        }
+    };
+
+    match device.pair().await {
+        Ok(()) => {
+            info!("Paired with {}", addr);
+            let _ = tx.send(ScanEvent::PairingSucceeded(addr));
+        }
+        Err(e) => {
+            warn!("Pairing with {} failed: {}", addr, e);
+            let _ = tx.send(ScanEvent::PairingFailed(addr, e.to_string()));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use log::{debug, info, warn};
+use tokio::runtime::Handle;
+use tokio::sync::RwLock;
+
+use crate::airpods::AirPodsDevice;
+
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER_IFACE: &str = "org.freedesktop.login1.Manager";
+
+/// Subscribes to logind's `PrepareForSleep` signal on the system bus and
+/// re-arms every live AirPods session's AACP notifications after resume,
+/// since the kernel typically tears the AACP L2CAP channel down across
+/// suspend and BlueZ doesn't always report that as a disconnect, so
+/// `AirPodsDevice` would otherwise never notice and battery/ear-detection/ANC
+/// notifications would silently stop. Spawns a dedicated thread (mirroring
+/// `ui::system_theme::spawn_listener`) since the blocking `Connection` needs
+/// to own the thread it processes on; `rt_handle` lets the signal callback
+/// hop back onto the Tokio runtime to await the async device calls.
+pub fn spawn_listener(active_airpods: Arc<RwLock<HashMap<String, Arc<AirPodsDevice>>>>, rt_handle: Handle) {
+    std::thread::spawn(move || {
+        let Ok(conn) = Connection::new_system() else {
+            warn!("Failed to connect to the system bus for suspend/resume handling");
+            return;
+        };
+
+        let rule = MatchRule::new_signal(LOGIND_MANAGER_IFACE, "PrepareForSleep");
+        let add_match_result = conn.add_match(rule, move |_: (), _, msg| {
+            if msg.path().as_deref() != Some(LOGIND_PATH) {
+                return true;
+            }
+            let Ok((going_to_sleep,)) = msg.read1::<bool>() else { return true; };
+
+            let active_airpods = active_airpods.clone();
+            if going_to_sleep {
+                info!("System is suspending, flushing AirPods media ownership state");
+                rt_handle.spawn(async move {
+                    for device in active_airpods.read().await.values() {
+                        device.prepare_for_sleep().await;
+                    }
+                });
+            } else {
+                info!("System resumed, re-arming AirPods AACP sessions");
+                rt_handle.spawn(async move {
+                    for device in active_airpods.read().await.values() {
+                        device.rearm().await;
+                    }
+                });
+            }
+            true
+        });
+        if let Err(e) = add_match_result {
+            warn!("Failed to subscribe to logind's PrepareForSleep signal: {}", e);
+            return;
+        }
+
+        debug!("Listening for suspend/resume via logind's PrepareForSleep signal...");
+        loop {
+            if let Err(e) = conn.process(Duration::from_millis(1000)) {
+                warn!("Suspend/resume listener D-Bus error: {}", e);
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bluer::{Address, Session};
+use log::{debug, warn};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::bluetooth::adapters;
+use crate::bluetooth::reconnect;
+use crate::devices::config;
+use crate::devices::enums::DeviceData;
+use crate::ui::messages::BluetoothUIMessage;
+
+/// Standard Bluetooth SIG Battery Service UUID. Any device that advertises
+/// it is a candidate for the generic battery-only device type. This watchdog
+/// only checks for it to log a debug line; a known device is reconnected
+/// whether or not it currently advertises this UUID.
+const BATTERY_SERVICE_UUID: &str = "0000180f-0000-1000-8000-00805f9b34fb";
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+fn known_devices() -> HashMap<String, DeviceData> {
+    config::load().flatten()
+}
+
+/// Background watchdog that keeps every auto-reconnect-enabled device
+/// recorded in `devices.json` connected. Unlike `reconnect::spawn_auto_reconnect`
+/// (which only fires once a connected device unexpectedly drops), this
+/// periodically looks up *every* known device's address on each powered
+/// adapter -- including ones that were never connected this run -- and treats
+/// any that BlueZ knows about but reports as not connected as a device to
+/// bring back. It does not start discovery itself. The actual connect
+/// attempt is handed off to `reconnect::spawn_auto_reconnect`, which already
+/// de-duplicates concurrent attempts per MAC and owns the backoff ladder, so
+/// this loop only needs to notice "still disconnected" and kick it off.
+pub async fn run(ui_tx: UnboundedSender<BluetoothUIMessage>) {
+    loop {
+        match try_scan_and_reconnect(&ui_tx).await {
+            Ok(()) => {}
+            Err(e) => warn!("Connection watchdog scan failed: {}", e),
+        }
+        tokio::time::sleep(SCAN_INTERVAL).await;
+    }
+}
+
+async fn try_scan_and_reconnect(ui_tx: &UnboundedSender<BluetoothUIMessage>) -> bluer::Result<()> {
+    let devices = known_devices();
+    if devices.is_empty() {
+        return Ok(());
+    }
+
+    let session = Session::new().await?;
+    let adapters = adapters::resolve(&session, adapters::selector()).await?;
+
+    for (mac, device_data) in devices {
+        if !device_data.auto_reconnect {
+            continue;
+        }
+        let Ok(addr) = mac.parse::<Address>() else { continue };
+
+        for adapter in &adapters {
+            if !adapter.is_powered().await.unwrap_or(false) {
+                continue;
+            }
+            let Ok(device) = adapter.device(addr) else { continue };
+            if device.is_connected().await.unwrap_or(false) {
+                continue;
+            }
+
+            let advertises_battery_service = device
+                .uuids()
+                .await
+                .ok()
+                .flatten()
+                .map(|uuids| uuids.iter().any(|u| u.to_string().eq_ignore_ascii_case(BATTERY_SERVICE_UUID)))
+                .unwrap_or(false);
+            // Known devices are reconnected by persisted address regardless of
+            // whether we can currently confirm the battery-service UUID (BlueZ
+            // doesn't always re-expose it until a fresh connection), but we log
+            // the mismatch since it's a useful signal something else is nearby
+            // and advertising the same address space.
+            if !advertises_battery_service {
+                debug!("{} not currently advertising the battery service; attempting reconnect anyway", mac);
+            }
+
+            reconnect::spawn_auto_reconnect(mac.clone(), ui_tx.clone());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use dbus::arg::{RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::channel::Sender;
+use dbus::message::{Message, MatchRule};
+use log::{debug, error, warn};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::bluetooth::aacp::ControlCommandIdentifiers;
+
+const BUS_NAME: &str = "org.librepods.Device";
+const DEVICE_IFACE: &str = "org.librepods.Device1";
+const PROPERTIES_IFACE: &str = "org.freedesktop.DBus.Properties";
+
+/// Mirrors the subset of `MyTray`'s fields scripts/status bars care about,
+/// kept in its own service so `--no-tray` users (and i3/sway status bars)
+/// have something to read/drive without going through `ksni`.
+#[derive(Default)]
+struct DeviceBusState {
+    /// `(level 0-100, status code)` per bud/case; status code matches
+    /// `BatteryStatus`'s ordering (0 = not charging, 1 = charging, 2 =
+    /// disconnected) -- see `airpods::battery_status_code`.
+    battery_left: Option<(u8, u8)>,
+    battery_right: Option<(u8, u8)>,
+    battery_case: Option<(u8, u8)>,
+    listening_mode: Option<u8>,
+    conversation_detect: Option<bool>,
+    connected: bool,
+    command_tx: Option<UnboundedSender<(ControlCommandIdentifiers, Vec<u8>)>>,
+}
+
+struct RegisteredDevice {
+    path: String,
+    state: Mutex<DeviceBusState>,
+}
+
+static DEVICES: OnceLock<Mutex<HashMap<String, RegisteredDevice>>> = OnceLock::new();
+
+fn devices() -> &'static Mutex<HashMap<String, RegisteredDevice>> {
+    DEVICES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+static CONN: OnceLock<Connection> = OnceLock::new();
+
+/// Lazily requests `org.librepods.Device` on the system bus and starts a
+/// background thread answering `org.freedesktop.DBus.Properties` calls and
+/// the `SetListeningMode`/`SetConversationDetect` methods for whichever
+/// devices are currently registered -- mirrors `status_export`'s own
+/// dedicated D-Bus connection.
+fn conn() -> Option<&'static Connection> {
+    if let Some(conn) = CONN.get() {
+        return Some(conn);
+    }
+
+    let conn = match Connection::new_system() {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to connect to the system bus for the device status/control service: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = conn.request_name(BUS_NAME, false, true, false) {
+        error!("Failed to request D-Bus name {}: {}", BUS_NAME, e);
+        return None;
+    }
+    conn.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(|msg, conn| {
+            handle_call(&msg, conn);
+            true
+        }),
+    );
+    if CONN.set(conn).is_err() {
+        return CONN.get();
+    }
+
+    std::thread::spawn(|| {
+        let conn = CONN.get().expect("device bus connection initialized above");
+        loop {
+            if let Err(e) = conn.process(Duration::from_millis(500)) {
+                error!("Device status/control service D-Bus error: {}", e);
+                break;
+            }
+        }
+    });
+    debug!("Device status/control service listening as {}", BUS_NAME);
+    CONN.get()
+}
+
+fn object_path(mac: &str) -> String {
+    format!("/org/librepods/Device/{}", mac.replace([':', '-'], "_"))
+}
+
+/// Registers `mac` as a live device, exposing `org.librepods.Device1` at
+/// `/org/librepods/Device/<mac>` and routing `SetListeningMode`/
+/// `SetConversationDetect` calls into `command_tx`, the same channel
+/// `AirPodsDevice::new`'s command pump already reads from.
+pub fn register(mac: &str, command_tx: UnboundedSender<(ControlCommandIdentifiers, Vec<u8>)>) {
+    let Some(_) = conn() else { return };
+
+    let mut devices = devices().lock().unwrap();
+    devices.insert(
+        mac.to_string(),
+        RegisteredDevice {
+            path: object_path(mac),
+            state: Mutex::new(DeviceBusState { connected: true, command_tx: Some(command_tx), ..Default::default() }),
+        },
+    );
+}
+
+/// Removes `mac`'s object from the bus during disconnect teardown.
+pub fn unregister(mac: &str) {
+    devices().lock().unwrap().remove(mac);
+}
+
+fn emit_changed(mac: &str, changes: Vec<(&str, Box<dyn RefArg>)>) {
+    let Some(conn) = CONN.get() else { return };
+    let devices = devices().lock().unwrap();
+    let Some(device) = devices.get(mac) else { return };
+    let path = device.path.clone();
+    drop(devices);
+
+    let mut changed: HashMap<String, Variant<Box<dyn RefArg>>> = HashMap::new();
+    for (name, value) in changes {
+        changed.insert(name.to_string(), Variant(value));
+    }
+    let Some(signal) = Message::new_signal(path.as_str(), PROPERTIES_IFACE, "PropertiesChanged") else { return };
+    let signal = signal.append3(DEVICE_IFACE, changed, Vec::<String>::new());
+    let _ = conn.channel().send(signal);
+}
+
+/// Updates one bud/case's level+status (see `DeviceBusState::battery_left`
+/// for the status code mapping) and emits `PropertiesChanged` for it.
+pub fn update_battery_left(mac: &str, level: u8, status: u8) {
+    if let Some(d) = devices().lock().unwrap().get(mac) {
+        d.state.lock().unwrap().battery_left = Some((level, status));
+    }
+    emit_changed(mac, vec![("BatteryLeft", Box::new((level, status)))]);
+}
+
+pub fn update_battery_right(mac: &str, level: u8, status: u8) {
+    if let Some(d) = devices().lock().unwrap().get(mac) {
+        d.state.lock().unwrap().battery_right = Some((level, status));
+    }
+    emit_changed(mac, vec![("BatteryRight", Box::new((level, status)))]);
+}
+
+pub fn update_battery_case(mac: &str, level: u8, status: u8) {
+    if let Some(d) = devices().lock().unwrap().get(mac) {
+        d.state.lock().unwrap().battery_case = Some((level, status));
+    }
+    emit_changed(mac, vec![("BatteryCase", Box::new((level, status)))]);
+}
+
+pub fn update_listening_mode(mac: &str, mode: u8) {
+    if let Some(d) = devices().lock().unwrap().get(mac) {
+        d.state.lock().unwrap().listening_mode = Some(mode);
+    }
+    emit_changed(mac, vec![("ListeningMode", Box::new(mode))]);
+}
+
+pub fn update_conversation_detect(mac: &str, enabled: bool) {
+    if let Some(d) = devices().lock().unwrap().get(mac) {
+        d.state.lock().unwrap().conversation_detect = Some(enabled);
+    }
+    emit_changed(mac, vec![("ConversationDetect", Box::new(enabled))]);
+}
+
+fn find_mac_for_path(path: &str) -> Option<String> {
+    devices().lock().unwrap().iter().find(|(_, d)| d.path == path).map(|(mac, _)| mac.clone())
+}
+
+fn handle_call(msg: &Message, conn: &Connection) {
+    let Some(path) = msg.path() else { return };
+    let path: &str = &path;
+    let Some(mac) = find_mac_for_path(path) else { return };
+
+    match msg.interface().as_deref() {
+        Some(PROPERTIES_IFACE) => handle_properties_call(msg, conn, &mac),
+        Some(DEVICE_IFACE) => handle_method_call(msg, conn, &mac),
+        _ => {}
+    }
+}
+
+fn properties_map(state: &DeviceBusState) -> HashMap<String, Variant<Box<dyn RefArg>>> {
+    let mut props: HashMap<String, Variant<Box<dyn RefArg>>> = HashMap::new();
+    if let Some(v) = state.battery_left {
+        props.insert("BatteryLeft".to_string(), Variant(Box::new(v)));
+    }
+    if let Some(v) = state.battery_right {
+        props.insert("BatteryRight".to_string(), Variant(Box::new(v)));
+    }
+    if let Some(v) = state.battery_case {
+        props.insert("BatteryCase".to_string(), Variant(Box::new(v)));
+    }
+    if let Some(v) = state.listening_mode {
+        props.insert("ListeningMode".to_string(), Variant(Box::new(v)));
+    }
+    if let Some(v) = state.conversation_detect {
+        props.insert("ConversationDetect".to_string(), Variant(Box::new(v)));
+    }
+    props.insert("Connected".to_string(), Variant(Box::new(state.connected)));
+    props
+}
+
+fn handle_properties_call(msg: &Message, conn: &Connection, mac: &str) {
+    let devices = devices().lock().unwrap();
+    let Some(device) = devices.get(mac) else { return };
+    let state = device.state.lock().unwrap();
+
+    let reply = match msg.member().as_deref() {
+        Some("GetAll") => msg.method_return().append1(properties_map(&state)),
+        Some("Get") => {
+            let Ok((_, property)) = msg.read2::<String, String>() else { return };
+            match property.as_str() {
+                "BatteryLeft" => match state.battery_left {
+                    Some(v) => msg.method_return().append1(Variant(v)),
+                    None => return,
+                },
+                "BatteryRight" => match state.battery_right {
+                    Some(v) => msg.method_return().append1(Variant(v)),
+                    None => return,
+                },
+                "BatteryCase" => match state.battery_case {
+                    Some(v) => msg.method_return().append1(Variant(v)),
+                    None => return,
+                },
+                "ListeningMode" => match state.listening_mode {
+                    Some(v) => msg.method_return().append1(Variant(v)),
+                    None => return,
+                },
+                "ConversationDetect" => match state.conversation_detect {
+                    Some(v) => msg.method_return().append1(Variant(v)),
+                    None => return,
+                },
+                "Connected" => msg.method_return().append1(Variant(state.connected)),
+                _ => return,
+            }
+        }
+        _ => return,
+    };
+    drop(state);
+    drop(devices);
+    let _ = conn.channel().send(reply);
+}
+
+fn handle_method_call(msg: &Message, conn: &Connection, mac: &str) {
+    let command_tx = {
+        let devices = devices().lock().unwrap();
+        devices.get(mac).and_then(|d| d.state.lock().unwrap().command_tx.clone())
+    };
+    let Some(command_tx) = command_tx else { return };
+
+    let reply = match msg.member().as_deref() {
+        Some("SetListeningMode") => {
+            let Ok((mode,)) = msg.read1::<u8>() else { return };
+            let _ = command_tx.send((ControlCommandIdentifiers::ListeningMode, vec![mode]));
+            msg.method_return()
+        }
+        Some("SetConversationDetect") => {
+            let Ok((enabled,)) = msg.read1::<bool>() else { return };
+            let _ = command_tx.send((ControlCommandIdentifiers::ConversationDetectConfig, vec![if enabled { 0x01 } else { 0x00 }]));
+            msg.method_return()
+        }
+        _ => {
+            warn!("Unknown method {:?} called on {}", msg.member(), mac);
+            return;
+        }
+    };
+    let _ = conn.channel().send(reply);
+}
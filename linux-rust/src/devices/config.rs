@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::SystemTime;
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+
+use crate::devices::enums::DeviceData;
+use crate::utils::get_devices_path;
+
+const CURRENT_VERSION: u32 = 2;
+
+/// Group newly-added devices land in when the user hasn't organized them
+/// into a custom group yet, and what the legacy flat file migrates into.
+pub const DEFAULT_GROUP: &str = "Devices";
+
+fn current_version() -> u32 {
+    CURRENT_VERSION
+}
+
+/// On-disk device config: a user-ordered list of groups, each holding its own
+/// devices. Replaces the old flat `HashMap<String, DeviceData>` (version-less,
+/// implicitly "version 1") -- `load()` transparently migrates that shape into
+/// a single `"Devices"` group the first time it's read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceConfig {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub groups: Vec<DeviceGroup>,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        Self { version: CURRENT_VERSION, groups: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceGroup {
+    pub name: String,
+    #[serde(default)]
+    pub devices: HashMap<String, DeviceEntry>,
+}
+
+/// A single paired device's config: the existing connection data plus the
+/// display overrides and per-device preferences this adds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceEntry {
+    #[serde(flatten)]
+    pub data: DeviceData,
+    /// Overrides `data.name` in the sidebar/tray if set.
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Emoji or icon name shown next to the device in the sidebar.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Freeform per-device settings (remembered noise-control mode,
+    /// ear-detection on/off, etc.) that don't warrant their own column.
+    #[serde(default)]
+    pub preferences: HashMap<String, serde_json::Value>,
+}
+
+impl DeviceEntry {
+    pub fn new(data: DeviceData) -> Self {
+        Self { data, display_name: None, icon: None, preferences: HashMap::new() }
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.data.name)
+    }
+}
+
+impl DeviceConfig {
+    /// Every device across every group as `(mac, group_name, entry)`, for
+    /// callers that want the full list without caring about grouping.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, &DeviceEntry)> {
+        self.groups.iter().flat_map(|g| {
+            g.devices.iter().map(move |(mac, entry)| (mac.as_str(), g.name.as_str(), entry))
+        })
+    }
+
+    pub fn get(&self, mac: &str) -> Option<&DeviceEntry> {
+        self.groups.iter().find_map(|g| g.devices.get(mac))
+    }
+
+    pub fn get_mut(&mut self, mac: &str) -> Option<&mut DeviceEntry> {
+        self.groups.iter_mut().find_map(|g| g.devices.get_mut(mac))
+    }
+
+    /// Removes `mac` from whichever group currently holds it.
+    pub fn remove(&mut self, mac: &str) -> Option<DeviceEntry> {
+        self.groups.iter_mut().find_map(|g| g.devices.remove(mac))
+    }
+
+    /// Inserts/overwrites `mac` in `group_name`, creating the group if it
+    /// doesn't exist yet.
+    pub fn insert(&mut self, group_name: &str, mac: String, entry: DeviceEntry) {
+        if let Some(group) = self.groups.iter_mut().find(|g| g.name == group_name) {
+            group.devices.insert(mac, entry);
+        } else {
+            let mut devices = HashMap::new();
+            devices.insert(mac, entry);
+            self.groups.push(DeviceGroup { name: group_name.to_string(), devices });
+        }
+    }
+
+    /// Collapses every group back into the old flat `HashMap<String,
+    /// DeviceData>` shape, for call sites that only care about connection
+    /// data and don't (yet) need grouping/display overrides.
+    pub fn flatten(&self) -> HashMap<String, DeviceData> {
+        self.iter().map(|(mac, _group, entry)| (mac.to_string(), entry.data.clone())).collect()
+    }
+}
+
+fn migrate_legacy(value: serde_json::Value) -> DeviceConfig {
+    let legacy: HashMap<String, DeviceData> = match serde_json::from_value(value) {
+        Ok(map) => map,
+        Err(e) => {
+            error!("Failed to parse legacy devices file during migration, starting fresh: {}", e);
+            return DeviceConfig::default();
+        }
+    };
+    let devices = legacy.into_iter().map(|(mac, data)| (mac, DeviceEntry::new(data))).collect();
+    DeviceConfig {
+        version: CURRENT_VERSION,
+        groups: vec![DeviceGroup { name: DEFAULT_GROUP.to_string(), devices }],
+    }
+}
+
+struct Cache {
+    config: DeviceConfig,
+    mtime: Option<SystemTime>,
+}
+
+static CACHE: OnceLock<RwLock<Cache>> = OnceLock::new();
+
+fn cache() -> &'static RwLock<Cache> {
+    CACHE.get_or_init(|| RwLock::new(Cache {
+        config: load_from_disk(),
+        mtime: current_mtime(),
+    }))
+}
+
+fn current_mtime() -> Option<SystemTime> {
+    std::fs::metadata(get_devices_path()).ok().and_then(|m| m.modified().ok())
+}
+
+/// Loads `devices.json`, migrating the legacy flat `HashMap<String,
+/// DeviceData>` shape (no top-level `"version"` key) into a single default
+/// group on the fly. The migrated shape is written straight back out so
+/// future loads skip the migration step.
+fn load_from_disk() -> DeviceConfig {
+    let path = get_devices_path();
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return DeviceConfig::default();
+    };
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&contents) else {
+        error!("Failed to parse devices file at {}, starting fresh", path.display());
+        return DeviceConfig::default();
+    };
+
+    if value.get("version").is_some() {
+        serde_json::from_value(value).unwrap_or_else(|e| {
+            error!("Failed to deserialize device config, starting fresh: {}", e);
+            DeviceConfig::default()
+        })
+    } else {
+        let migrated = migrate_legacy(value);
+        // Not `save()`: this runs from inside the cache's own lazy
+        // initializer, and `save()` re-enters `cache()`, which would
+        // deadlock on `CACHE.get_or_init`.
+        write_to_disk(&migrated);
+        migrated
+    }
+}
+
+/// Returns the current device config. Reads from the in-memory cache unless
+/// the devices file's mtime has changed since the last load (e.g. an
+/// external edit), in which case it transparently reloads. `view()` calls
+/// this on every render (it's driven by the 100ms `MicLevelTick`
+/// subscription); only the mtime check runs on each call, so the cache
+/// spares a disk read and a full JSON parse ~10 times a second.
+pub fn load() -> DeviceConfig {
+    let lock = cache();
+    let mtime_now = current_mtime();
+    {
+        let guard = lock.read().unwrap();
+        if guard.mtime == mtime_now {
+            return guard.config.clone();
+        }
+    }
+    let mut guard = lock.write().unwrap();
+    debug!("Devices file changed on disk, reloading");
+    guard.config = load_from_disk();
+    guard.mtime = mtime_now;
+    guard.config.clone()
+}
+
+/// Writes `config` to `devices.json` via a temp file and rename, so a crash
+/// mid-write can't corrupt it. Does not touch the cache -- see `save()`.
+fn write_to_disk(config: &DeviceConfig) {
+    let path = get_devices_path();
+    let json = match serde_json::to_string(config) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Failed to serialize device config: {}", e);
+            return;
+        }
+    };
+    let tmp_path = path.with_extension("json.tmp");
+    if let Err(e) = std::fs::write(&tmp_path, &json).and_then(|_| std::fs::rename(&tmp_path, &path)) {
+        error!("Failed to write device config to {}: {}", path.display(), e);
+    }
+}
+
+/// Persists `config` to `devices.json` and updates the in-memory cache
+/// immediately.
+pub fn save(config: &DeviceConfig) {
+    write_to_disk(config);
+    let mut guard = cache().write().unwrap();
+    guard.mtime = current_mtime();
+    guard.config = config.clone();
+}
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 use serde::{Deserialize, Serialize};
+use crate::bluetooth::aacp::BatteryComponent;
 use crate::devices::airpods::AirPodsInformation;
 use crate::devices::nothing::NothingInformation;
 
@@ -7,7 +8,10 @@ use crate::devices::nothing::NothingInformation;
 #[derive(PartialEq)]
 pub enum DeviceType {
     AirPods,
-    Nothing
+    Nothing,
+    /// Any device whose only known protocol is the standard GATT Battery
+    /// Service (0x180F) -- no vendor-specific decoding available.
+    GenericBle,
 }
 
 impl Display for DeviceType {
@@ -15,6 +19,7 @@ impl Display for DeviceType {
         match self {
             DeviceType::AirPods => write!(f, "AirPods"),
             DeviceType::Nothing => write!(f, "Nothing"),
+            DeviceType::GenericBle => write!(f, "Generic BLE Device"),
         }
     }
 }
@@ -32,6 +37,10 @@ pub struct DeviceData {
     pub name: String,
     pub type_: DeviceType,
     pub information: Option<DeviceInformation>,
+    /// Opt-in: if the device drops out, keep retrying the connection with
+    /// backoff instead of waiting for the user (or BlueZ) to reconnect it.
+    #[serde(default)]
+    pub auto_reconnect: bool,
 }
 
 
@@ -39,6 +48,7 @@ pub struct DeviceData {
 pub enum DeviceState {
     AirPods(AirPodsState),
     Nothing(NothingState),
+    GenericBle(GenericBleState),
 }
 
 impl Display for DeviceState {
@@ -46,21 +56,75 @@ impl Display for DeviceState {
         match self {
             DeviceState::AirPods(_) => write!(f, "AirPods State"),
             DeviceState::Nothing(_) => write!(f, "Nothing State"),
+            DeviceState::GenericBle(_) => write!(f, "Generic BLE State"),
         }
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct GenericBleState {
+    pub battery: Vec<BatteryComponent>,
+}
+
 #[derive(Clone, Debug)]
 pub struct AirPodsState {
+    pub device_name: String,
+    pub battery: Vec<BatteryComponent>,
+    pub noise_control_mode: AirPodsNoiseControlMode,
+    pub noise_control_state: iced::widget::combo_box::State<AirPodsNoiseControlMode>,
     pub conversation_awareness_enabled: bool,
+    pub personalized_volume_enabled: bool,
+    pub allow_off_mode: bool,
+    pub ear_detection_auto_pause_enabled: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AirPodsNoiseControlMode {
+    Off,
+    NoiseCancellation,
+    Transparency,
+    Adaptive,
+}
+
+impl Display for AirPodsNoiseControlMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AirPodsNoiseControlMode::Off => write!(f, "Off"),
+            AirPodsNoiseControlMode::NoiseCancellation => write!(f, "Noise Cancellation"),
+            AirPodsNoiseControlMode::Transparency => write!(f, "Transparency"),
+            AirPodsNoiseControlMode::Adaptive => write!(f, "Adaptive"),
+        }
+    }
+}
+
+impl AirPodsNoiseControlMode {
+    pub fn from_byte(value: u8) -> Self {
+        match value {
+            0x01 => AirPodsNoiseControlMode::Off,
+            0x02 => AirPodsNoiseControlMode::NoiseCancellation,
+            0x03 => AirPodsNoiseControlMode::Transparency,
+            0x04 => AirPodsNoiseControlMode::Adaptive,
+            _ => AirPodsNoiseControlMode::Transparency,
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            AirPodsNoiseControlMode::Off => 0x01,
+            AirPodsNoiseControlMode::NoiseCancellation => 0x02,
+            AirPodsNoiseControlMode::Transparency => 0x03,
+            AirPodsNoiseControlMode::Adaptive => 0x04,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct NothingState {
     pub anc_mode: NothingAncMode,
+    pub anc_mode_state: iced::widget::combo_box::State<NothingAncMode>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum NothingAncMode {
     Off,
     LowNoiseCancellation,
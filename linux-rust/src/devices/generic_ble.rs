@@ -0,0 +1,100 @@
+use bluer::{Address, Device, Session};
+use futures::StreamExt;
+use log::{debug, error, info};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::bluetooth::adapters;
+use crate::bluetooth::aacp::{BatteryComponent, BatteryStatus};
+use crate::ui::messages::BluetoothUIMessage;
+
+/// Standard Bluetooth SIG Battery Service / Battery Level characteristic.
+/// `bluetooth::watchdog` checks for the same service UUID, but only to log
+/// whether a known device currently advertises it.
+const BATTERY_SERVICE_UUID: &str = "0000180f-0000-1000-8000-00805f9b34fb";
+const BATTERY_LEVEL_CHAR_UUID: &str = "00002a19-0000-1000-8000-00805f9b34fb";
+
+/// A connected device we know nothing about beyond the standard GATT
+/// Battery Service. Unlike `AirPodsDevice`/`NothingDevice`, there's no
+/// vendor manager to hand back to `DeviceManagers` -- the battery level is
+/// read once and then streamed over `ui_tx` as it changes.
+pub struct GenericBleDevice {
+    pub address: Address,
+}
+
+impl GenericBleDevice {
+    pub async fn new(address: Address, ui_tx: UnboundedSender<BluetoothUIMessage>) -> Self {
+        let addr_str = address.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = monitor_battery(address, addr_str.clone(), ui_tx).await {
+                error!("Generic BLE battery monitor for {} stopped: {}", addr_str, e);
+            }
+        });
+
+        Self { address }
+    }
+}
+
+async fn find_battery_level_characteristic(device: &Device) -> bluer::Result<Option<bluer::gatt::remote::Characteristic>> {
+    for service in device.services().await? {
+        let uuid = service.uuid().await?;
+        if !uuid.to_string().eq_ignore_ascii_case(BATTERY_SERVICE_UUID) {
+            continue;
+        }
+        for characteristic in service.characteristics().await? {
+            let char_uuid = characteristic.uuid().await?;
+            if char_uuid.to_string().eq_ignore_ascii_case(BATTERY_LEVEL_CHAR_UUID) {
+                return Ok(Some(characteristic));
+            }
+        }
+    }
+    Ok(None)
+}
+
+async fn monitor_battery(address: Address, addr_str: String, ui_tx: UnboundedSender<BluetoothUIMessage>) -> bluer::Result<()> {
+    let session = Session::new().await?;
+    // Resolve against the --adapter selector main.rs bound to rather than
+    // BlueZ's "default" adapter, and prefer whichever candidate actually
+    // knows this address, so a device paired to a non-default adapter is
+    // still found.
+    let candidates = adapters::resolve(&session, adapters::selector()).await?;
+    let mut adapter = candidates.first().cloned();
+    for candidate in &candidates {
+        if candidate.device_addresses().await.unwrap_or_default().contains(&address) {
+            adapter = Some(candidate.clone());
+            break;
+        }
+    }
+    let adapter = adapter.ok_or_else(|| bluer::Error {
+        kind: bluer::ErrorKind::NotFound,
+        message: "No Bluetooth adapter available".to_string(),
+    })?;
+    let device = adapter.device(address)?;
+
+    let Some(characteristic) = find_battery_level_characteristic(&device).await? else {
+        error!("{} does not expose the Battery Service; nothing to monitor", addr_str);
+        return Ok(());
+    };
+
+    if let Ok(value) = characteristic.read().await {
+        report_level(&addr_str, &value, &ui_tx);
+    }
+
+    info!("Subscribed to battery level notifications for {}", addr_str);
+    let mut notify = characteristic.notify().await?;
+    while let Some(value) = notify.next().await {
+        report_level(&addr_str, &value, &ui_tx);
+    }
+
+    debug!("Battery level notification stream for {} ended", addr_str);
+    Ok(())
+}
+
+fn report_level(addr_str: &str, value: &[u8], ui_tx: &UnboundedSender<BluetoothUIMessage>) {
+    let Some(&level) = value.first() else { return };
+    let battery = vec![BatteryComponent {
+        component: BatteryComponent::Headphone,
+        level,
+        status: BatteryStatus::NotCharging,
+    }];
+    let _ = ui_tx.send(BluetoothUIMessage::BatteryLevelChanged(addr_str.to_string(), battery));
+}
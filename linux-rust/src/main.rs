@@ -1,11 +1,17 @@
+mod audio;
 mod bluetooth;
+mod device_bus;
+mod media;
 mod media_controller;
+mod mic_meter;
+mod settings;
+mod status_export;
 mod ui;
 mod utils;
 mod devices;
 
 use std::env;
-use log::info;
+use log::{debug, info};
 use dbus::blocking::Connection;
 use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
 use dbus::message::MatchRule;
@@ -21,10 +27,8 @@ use clap::Parser;
 use crate::bluetooth::le::start_le_monitor;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::RwLock;
-use crate::bluetooth::managers::DeviceManagers;
-use crate::devices::enums::DeviceData;
+use crate::bluetooth::managers::{device_key, DeviceManagers};
 use crate::ui::messages::BluetoothUIMessage;
-use crate::utils::get_devices_path;
 
 #[derive(Parser)]
 struct Args {
@@ -37,7 +41,19 @@ struct Args {
     #[arg(long, help="Enable Bluetooth LE debug logging. Only use when absolutely necessary; this produces a lot of logs.")]
     le_debug: bool,
     #[arg(long, short='v', help="Show application version and exit")]
-    version: bool
+    version: bool,
+    #[arg(long, help="Print i3bar-protocol JSON blocks for device battery/status to stdout, for use as an i3bar/swaybar status command")]
+    i3bar: bool,
+    #[arg(long, help="Bind to a specific Bluetooth adapter (e.g. hci0, or its MAC address) instead of monitoring every powered adapter")]
+    adapter: Option<String>,
+}
+
+/// Pulls the `hciN` adapter name out of a BlueZ device object path
+/// (`/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF`), so the system-wide
+/// `PropertiesChanged` watcher below can qualify `device_managers`/
+/// `active_airpods` keys by which adapter reported the event.
+fn adapter_name_from_path(path: &str) -> Option<&str> {
+    path.strip_prefix("/org/bluez/")?.split('/').next()
 }
 
 fn main() -> iced::Result {
@@ -58,39 +74,42 @@ fn main() -> iced::Result {
     }
     env_logger::init();
 
+    if args.i3bar {
+        status_export::set_i3bar_mode(true);
+    }
+
     let (ui_tx, ui_rx) = unbounded_channel::<BluetoothUIMessage>();
 
     let device_managers: Arc<RwLock<HashMap<String, DeviceManagers>>> = Arc::new(RwLock::new(HashMap::new()));
     let device_managers_clone = device_managers.clone();
+    // Live `AirPodsDevice` sessions, keyed by `device_key(adapter, mac)` (the
+    // same earbuds can be paired to more than one adapter), so a disconnect
+    // can find the instance to `shutdown()` instead of just dropping a
+    // dangling `device_managers` entry.
+    let active_airpods: Arc<RwLock<HashMap<String, Arc<AirPodsDevice>>>> = Arc::new(RwLock::new(HashMap::new()));
+    let ui_tx_for_ui = ui_tx.clone();
     std::thread::spawn(|| {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async_main(ui_tx, device_managers_clone)).unwrap();
+        rt.block_on(async_main(ui_tx, device_managers_clone, active_airpods)).unwrap();
     });
 
-    ui::window::start_ui(ui_rx, args.start_minimized, device_managers)
+    ui::window::start_ui(ui_rx, ui_tx_for_ui, args.start_minimized, device_managers)
 }
 
 
 async fn async_main(
     ui_tx: tokio::sync::mpsc::UnboundedSender<BluetoothUIMessage>,
     device_managers: Arc<RwLock<HashMap<String, DeviceManagers>>>,
+    active_airpods: Arc<RwLock<HashMap<String, Arc<AirPodsDevice>>>>,
 ) -> bluer::Result<()> {
     let args = Args::parse();
 
     let mut managed_devices_mac: Vec<String> = Vec::new(); // includes ony non-AirPods. AirPods handled separately.
 
-    let devices_path = get_devices_path();
-    let devices_json = std::fs::read_to_string(&devices_path).unwrap_or_else(|e| {
-        log::error!("Failed to read devices file: {}", e);
-        "{}".to_string()
-    });
-    let devices_list: HashMap<String, DeviceData> = serde_json::from_str(&devices_json).unwrap_or_else(|e| {
-        log::error!("Deserialization failed: {}", e);
-        HashMap::new()
-    });
+    let devices_list = devices::config::load().flatten();
     for (mac, device_data) in devices_list.iter() {
         match device_data.type_ {
-            devices::enums::DeviceType::Nothing => {
+            devices::enums::DeviceType::Nothing | devices::enums::DeviceType::GenericBle => {
                 managed_devices_mac.push(mac.clone());
             }
             _ => {}
@@ -111,6 +130,9 @@ async fn async_main(
             battery_c: None,
             battery_c_status: None,
             connected: false,
+            mac_address: None,
+            device_name: None,
+            proximity: None,
             listening_mode: None,
             allow_off_option: None,
             command_tx: None,
@@ -120,85 +142,115 @@ async fn async_main(
         Some(handle)
     };
 
-    let session = bluer::Session::new().await?;
-    let adapter = session.default_adapter().await?;
-    adapter.set_powered(true).await?;
+    let session = Arc::new(bluer::Session::new().await?);
+    if let Ok(all) = bluetooth::adapters::list(&session).await {
+        debug!("Detected Bluetooth adapters: {:?}", all);
+    }
+    bluetooth::adapters::set_selector(args.adapter.clone());
+    let adapters = bluetooth::adapters::resolve(&session, args.adapter.as_deref()).await?;
+    info!("Monitoring {} Bluetooth adapter(s): {}", adapters.len(), adapters.iter().map(|a| a.name()).collect::<Vec<_>>().join(", "));
+
+    for adapter in &adapters {
+        let le_tray_clone = tray_handle.clone();
+        let le_adapter = adapter.clone();
+        tokio::spawn(async move {
+            info!("Starting LE monitor on {}...", le_adapter.name());
+            if let Err(e) = start_le_monitor(le_adapter, le_tray_clone).await {
+                log::error!("LE monitor error: {}", e);
+            }
+        });
+    }
 
-    let le_tray_clone = tray_handle.clone();
+    let watchdog_ui_tx = ui_tx.clone();
     tokio::spawn(async move {
-        info!("Starting LE monitor...");
-        if let Err(e) = start_le_monitor(le_tray_clone).await {
-            log::error!("LE monitor error: {}", e);
-        }
+        info!("Starting connection watchdog...");
+        bluetooth::watchdog::run(watchdog_ui_tx).await;
     });
 
+    bluetooth::suspend::spawn_listener(active_airpods.clone(), tokio::runtime::Handle::current());
+
     info!("Listening for new connections.");
 
     info!("Checking for connected devices...");
-    match find_connected_airpods(&adapter).await {
-        Ok(device) => {
-            let name = device.name().await?.unwrap_or_else(|| "Unknown".to_string());
-            info!("Found connected AirPods: {}, initializing.", name);
-            let airpods_device = AirPodsDevice::new(device.address(), tray_handle.clone(), ui_tx.clone()).await;
-
-            let mut managers = device_managers.write().await;
-            // let dev_managers = DeviceManagers::with_both(airpods_device.aacp_manager.clone(), airpods_device.att_manager.clone());
-            let dev_managers = DeviceManagers::with_aacp(airpods_device.aacp_manager.clone());
-            managers
-                .entry(device.address().to_string())
-                .or_insert(dev_managers)
-                .set_aacp(airpods_device.aacp_manager);
-            drop(managers);
-            ui_tx.send(BluetoothUIMessage::DeviceConnected(device.address().to_string())).unwrap();
-        }
-        Err(_) => {
-            info!("No connected AirPods found.");
-        }
-    }
+    for adapter in &adapters {
+        match find_connected_airpods(adapter).await {
+            Ok(device) => {
+                let name = device.name().await?.unwrap_or_else(|| "Unknown".to_string());
+                info!("Found connected AirPods: {}, initializing.", name);
+                let airpods_device = Arc::new(AirPodsDevice::new(device.address(), adapter.clone(), tray_handle.clone(), ui_tx.clone()).await);
 
-    match find_other_managed_devices(&adapter, managed_devices_mac.clone()).await {
-        Ok(devices) => {
-            for device in devices {
+                let mut managers = device_managers.write().await;
+                // let dev_managers = DeviceManagers::with_both(airpods_device.aacp_manager.clone(), airpods_device.att_manager.clone());
+                let dev_managers = DeviceManagers::with_aacp(airpods_device.aacp_manager.clone());
+                let key = device_key(adapter.name(), &device.address().to_string());
+                managers
+                    .entry(key.clone())
+                    .or_insert(dev_managers)
+                    .set_aacp(airpods_device.aacp_manager.clone());
+                drop(managers);
                 let addr_str = device.address().to_string();
-                info!("Found connected managed device: {}, initializing.", addr_str);
-                let type_ = devices_list.get(&addr_str).unwrap().type_.clone();
-                let ui_tx_clone = ui_tx.clone();
-                let device_managers = device_managers.clone();
+                active_airpods.write().await.insert(key, airpods_device);
+                ui_tx.send(BluetoothUIMessage::DeviceConnected(addr_str.clone())).unwrap();
                 tokio::spawn(async move {
-                    let mut managers = device_managers.write().await;
-                    match type_ {
-                        devices::enums::DeviceType::Nothing => {
-                            let dev = devices::nothing::NothingDevice::new(device.address(), ui_tx_clone.clone()).await;
-                            let dev_managers = DeviceManagers::with_att(dev.att_manager.clone());
-                            managers
-                                .entry(addr_str.clone())
-                                .or_insert(dev_managers)
-                                .set_att(dev.att_manager);
-                            ui_tx_clone.send(BluetoothUIMessage::DeviceConnected(addr_str)).unwrap();
-                        }
-                        _ => {}
-                    }
-                    drop(managers)
+                    crate::audio::on_device_connected(&addr_str).await;
                 });
             }
+            Err(_) => {
+                info!("No connected AirPods found on {}.", adapter.name());
+            }
         }
-        Err(e) => {
-            log::debug!("type of error: {:?}", e.kind);
-            if e.kind != bluer::ErrorKind::Internal(InternalErrorKind::Io(std::io::ErrorKind::NotFound)) {
-                log::error!("Error finding other managed devices: {}", e);
-            } else {
-                info!("No other managed devices found.");
+
+        match find_other_managed_devices(adapter, managed_devices_mac.clone()).await {
+            Ok(devices) => {
+                for device in devices {
+                    let addr_str = device.address().to_string();
+                    info!("Found connected managed device: {}, initializing.", addr_str);
+                    let type_ = devices_list.get(&addr_str).unwrap().type_.clone();
+                    let ui_tx_clone = ui_tx.clone();
+                    let device_managers = device_managers.clone();
+                    let key = device_key(adapter.name(), &addr_str);
+                    tokio::spawn(async move {
+                        let mut managers = device_managers.write().await;
+                        match type_ {
+                            devices::enums::DeviceType::Nothing => {
+                                let dev = devices::nothing::NothingDevice::new(device.address(), ui_tx_clone.clone()).await;
+                                let dev_managers = DeviceManagers::with_att(dev.att_manager.clone());
+                                managers
+                                    .entry(key)
+                                    .or_insert(dev_managers)
+                                    .set_att(dev.att_manager);
+                                ui_tx_clone.send(BluetoothUIMessage::DeviceConnected(addr_str)).unwrap();
+                            }
+                            devices::enums::DeviceType::GenericBle => {
+                                devices::generic_ble::GenericBleDevice::new(device.address(), ui_tx_clone.clone()).await;
+                                ui_tx_clone.send(BluetoothUIMessage::DeviceConnected(addr_str)).unwrap();
+                            }
+                            _ => {}
+                        }
+                        drop(managers)
+                    });
+                }
+            }
+            Err(e) => {
+                log::debug!("type of error: {:?}", e.kind);
+                if e.kind != bluer::ErrorKind::Internal(InternalErrorKind::Io(std::io::ErrorKind::NotFound)) {
+                    log::error!("Error finding other managed devices: {}", e);
+                } else {
+                    info!("No other managed devices found on {}.", adapter.name());
+                }
             }
         }
     }
 
     let conn = Connection::new_system()?;
     let rule = MatchRule::new_signal("org.freedesktop.DBus.Properties", "PropertiesChanged");
+    let session_for_watcher = session.clone();
     conn.add_match(rule, move |_: (), conn, msg| {
         let Some(path) = msg.path() else { return true; };
         if !path.contains("/org/bluez/hci") || !path.contains("/dev_") {
             return true;
         }
+        let Some(adapter_name) = adapter_name_from_path(&path).map(|s| s.to_string()) else { return true; };
         // debug!("PropertiesChanged signal for path: {}", path);
         let Ok((iface, changed, _)) = msg.read3::<String, HashMap<String, Variant<Box<dyn RefArg>>>, Vec<String>>() else {
             return true;
@@ -208,10 +260,26 @@ async fn async_main(
         }
         let Some(connected_var) = changed.get("Connected") else { return true; };
         let Some(is_connected) = connected_var.0.as_ref().as_u64() else { return true; };
+        let proxy = conn.with_proxy("org.bluez", path, std::time::Duration::from_millis(5000));
         if is_connected == 0 {
+            let Ok(addr_str) = proxy.get::<String>("org.bluez.Device1", "Address") else { return true; };
+            if devices_list.contains_key(&addr_str) {
+                info!("Managed device disconnected: {}", addr_str);
+                let _ = ui_tx.send(BluetoothUIMessage::DeviceDisconnected(addr_str.clone()));
+            }
+
+            let key = device_key(&adapter_name, &addr_str);
+            let active_airpods = active_airpods.clone();
+            let device_managers = device_managers.clone();
+            tokio::spawn(async move {
+                let device = active_airpods.write().await.remove(&key);
+                if let Some(device) = device {
+                    info!("Tearing down AirPods session for {} after disconnect", addr_str);
+                    device.shutdown(&device_managers).await;
+                }
+            });
             return true;
         }
-        let proxy = conn.with_proxy("org.bluez", path, std::time::Duration::from_millis(5000));
         let Ok(uuids) = proxy.get::<Vec<String>>("org.bluez.Device1", "UUIDs") else { return true; };
         let target_uuid = "74ec2172-0bad-4d01-8f77-997b2be0722a";
 
@@ -221,6 +289,7 @@ async fn async_main(
         if managed_devices_mac.contains(&addr_str) {
             info!("Managed device connected: {}, initializing", addr_str);
             let type_ = devices_list.get(&addr_str).unwrap().type_.clone();
+            let key = device_key(&adapter_name, &addr_str);
             match type_ {
                 devices::enums::DeviceType::Nothing => {
                     let ui_tx_clone = ui_tx.clone();
@@ -230,13 +299,20 @@ async fn async_main(
                         let dev = devices::nothing::NothingDevice::new(addr, ui_tx_clone.clone()).await;
                         let dev_managers = DeviceManagers::with_att(dev.att_manager.clone());
                         managers
-                            .entry(addr_str.clone())
+                            .entry(key)
                             .or_insert(dev_managers)
                             .set_att(dev.att_manager);
                         drop(managers);
                         ui_tx_clone.send(BluetoothUIMessage::DeviceConnected(addr_str.clone())).unwrap();
                     });
                 }
+                devices::enums::DeviceType::GenericBle => {
+                    let ui_tx_clone = ui_tx.clone();
+                    tokio::spawn(async move {
+                        devices::generic_ble::GenericBleDevice::new(addr, ui_tx_clone.clone()).await;
+                        ui_tx_clone.send(BluetoothUIMessage::DeviceConnected(addr_str.clone())).unwrap();
+                    });
+                }
                 _ => {}
             }
             return true;
@@ -245,22 +321,34 @@ async fn async_main(
         if !uuids.iter().any(|u| u.to_lowercase() == target_uuid) {
             return true;
         }
+        let Ok(adapter) = session_for_watcher.adapter(&adapter_name) else { return true; };
         let name = proxy.get::<String>("org.bluez.Device1", "Name").unwrap_or_else(|_| "Unknown".to_string());
         info!("AirPods connected: {}, initializing", name);
         let handle_clone = tray_handle.clone();
         let ui_tx_clone = ui_tx.clone();
         let device_managers = device_managers.clone();
+        let active_airpods = active_airpods.clone();
+        let key = device_key(&adapter_name, &addr_str);
         tokio::spawn(async move {
-            let airpods_device = AirPodsDevice::new(addr, handle_clone, ui_tx_clone.clone()).await;
+            // A stale session from before this disconnect/reconnect cycle
+            // shouldn't keep running underneath the fresh one below.
+            if let Some(old_device) = active_airpods.write().await.remove(&key) {
+                debug!("Replacing existing AirPods session for {} before reconnect", addr_str);
+                old_device.shutdown(&device_managers).await;
+            }
+
+            let airpods_device = Arc::new(AirPodsDevice::new(addr, adapter, handle_clone, ui_tx_clone.clone()).await);
             let mut managers = device_managers.write().await;
             // let dev_managers = DeviceManagers::with_both(airpods_device.aacp_manager.clone(), airpods_device.att_manager.clone());
             let dev_managers = DeviceManagers::with_aacp(airpods_device.aacp_manager.clone());
             managers
-                .entry(addr_str.clone())
+                .entry(key.clone())
                 .or_insert(dev_managers)
-                .set_aacp(airpods_device.aacp_manager);
+                .set_aacp(airpods_device.aacp_manager.clone());
             drop(managers);
+            active_airpods.write().await.insert(key, airpods_device);
             ui_tx_clone.send(BluetoothUIMessage::DeviceConnected(addr_str.clone())).unwrap();
+            crate::audio::on_device_connected(&addr_str).await;
         });
         true
     })?;
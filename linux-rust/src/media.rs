@@ -0,0 +1,181 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use dbus::blocking::Connection;
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use log::{debug, error};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+fn get_preferences_path() -> PathBuf {
+    let config_dir = std::env::var("XDG_CONFIG_HOME")
+        .unwrap_or_else(|_| format!("{}/.config", std::env::var("HOME").unwrap_or_default()));
+    PathBuf::from(config_dir).join("librepods").join("preferences.json")
+}
+
+/// Per-device opt-outs for ear-detection media control, read from
+/// `preferences.json` under the device's MAC as `pauseOnRemoval` and
+/// `resumeOnInsertion` booleans; a missing key counts as enabled.
+struct EarDetectionPrefs {
+    pause_on_removal: bool,
+    resume_on_insertion: bool,
+}
+
+fn device_prefs(mac: &str) -> EarDetectionPrefs {
+    let preferences: serde_json::Value =
+        std::fs::read_to_string(get_preferences_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+    let pref = |key: &str| preferences.get(mac).and_then(|p| p.get(key)).and_then(|b| b.as_bool()).unwrap_or(true);
+    EarDetectionPrefs {
+        pause_on_removal: pref("pauseOnRemoval"),
+        resume_on_insertion: pref("resumeOnInsertion"),
+    }
+}
+
+/// Pauses/resumes MPRIS media players in response to earbud ear-detection
+/// state, remembering which players it paused so it only resumes those.
+pub struct EarDetectionMediaController {
+    paused_by_us: Arc<Mutex<HashSet<String>>>,
+    generation: Arc<Mutex<u64>>,
+    last_state: Mutex<Option<(bool, bool)>>,
+}
+
+impl EarDetectionMediaController {
+    pub fn new() -> Self {
+        Self {
+            paused_by_us: Arc::new(Mutex::new(HashSet::new())),
+            generation: Arc::new(Mutex::new(0)),
+            last_state: Mutex::new(None),
+        }
+    }
+
+    /// Call on every in-ear status update, from either the AACP notification
+    /// path or the LE-advertisement decode path. Debounces rapid in/out
+    /// transitions so a bud wobbling in the ear doesn't spam MPRIS, and
+    /// ignores a repeat of the last-seen state so a stale/duplicate
+    /// advertisement can't re-trigger an action.
+    pub async fn on_ear_status_changed_for(&self, mac: &str, left_in_ear: bool, right_in_ear: bool) {
+        {
+            let mut last = self.last_state.lock().await;
+            if *last == Some((left_in_ear, right_in_ear)) {
+                return;
+            }
+            *last = Some((left_in_ear, right_in_ear));
+        }
+
+        let both_out = !left_in_ear && !right_in_ear;
+        let prefs = device_prefs(mac);
+        if (both_out && !prefs.pause_on_removal) || (!both_out && !prefs.resume_on_insertion) {
+            return;
+        }
+
+        let paused_by_us = self.paused_by_us.clone();
+        let generation = self.generation.clone();
+
+        let this_generation = {
+            let mut gen = generation.lock().await;
+            *gen += 1;
+            *gen
+        };
+
+        tokio::spawn(async move {
+            sleep(DEBOUNCE).await;
+            if *generation.lock().await != this_generation {
+                // a newer transition came in while we were debouncing
+                return;
+            }
+
+            let result = tokio::task::spawn_blocking(move || {
+                if both_out {
+                    pause_playing_players(&paused_by_us)
+                } else {
+                    resume_paused_players(&paused_by_us)
+                }
+            })
+            .await;
+
+            if let Err(e) = result {
+                error!("Failed to join MPRIS control task: {}", e);
+            }
+        });
+    }
+}
+
+static CONTROLLERS: OnceLock<std::sync::Mutex<HashMap<String, Arc<EarDetectionMediaController>>>> = OnceLock::new();
+
+/// Returns the shared ear-detection controller for `mac`, creating one if
+/// this is the first time we've seen this device. Shared between the AACP
+/// and LE-advertisement code paths so they debounce and dedupe together
+/// instead of fighting each other over the same media players.
+pub fn controller_for(mac: &str) -> Arc<EarDetectionMediaController> {
+    let mut controllers = CONTROLLERS.get_or_init(|| std::sync::Mutex::new(HashMap::new())).lock().unwrap();
+    controllers
+        .entry(mac.to_string())
+        .or_insert_with(|| Arc::new(EarDetectionMediaController::new()))
+        .clone()
+}
+
+fn list_mpris_players(conn: &Connection) -> Vec<String> {
+    let proxy = conn.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", Duration::from_millis(2000));
+    let (names,): (Vec<String>,) = match proxy.method_call("org.freedesktop.DBus", "ListNames", ()) {
+        Ok(r) => r,
+        Err(e) => {
+            error!("Failed to list D-Bus names for MPRIS discovery: {}", e);
+            return Vec::new();
+        }
+    };
+    names.into_iter().filter(|n| n.starts_with(MPRIS_PREFIX)).collect()
+}
+
+fn is_playing(conn: &Connection, dest: &str) -> bool {
+    let proxy = conn.with_proxy(dest, "/org/mpris/MediaPlayer2", Duration::from_millis(2000));
+    matches!(
+        proxy.get::<String>("org.mpris.MediaPlayer2.Player", "PlaybackStatus"),
+        Ok(status) if status == "Playing"
+    )
+}
+
+fn pause_playing_players(paused_by_us: &Arc<Mutex<HashSet<String>>>) {
+    let Ok(conn) = Connection::new_session() else {
+        error!("Failed to connect to session bus for MPRIS pause");
+        return;
+    };
+    let mut to_mark = Vec::new();
+    for dest in list_mpris_players(&conn) {
+        if !is_playing(&conn, &dest) {
+            continue;
+        }
+        let proxy = conn.with_proxy(&dest, "/org/mpris/MediaPlayer2", Duration::from_millis(2000));
+        match proxy.method_call::<(), _, _, _>("org.mpris.MediaPlayer2.Player", "Pause", ()) {
+            Ok(()) => {
+                debug!("Paused {} for ear-detection out-of-ear", dest);
+                to_mark.push(dest);
+            }
+            Err(e) => error!("Failed to pause {}: {}", dest, e),
+        }
+    }
+    paused_by_us.blocking_lock().extend(to_mark);
+}
+
+fn resume_paused_players(paused_by_us: &Arc<Mutex<HashSet<String>>>) {
+    let Ok(conn) = Connection::new_session() else {
+        error!("Failed to connect to session bus for MPRIS resume");
+        return;
+    };
+    let mut players = paused_by_us.blocking_lock();
+    for dest in players.drain() {
+        let proxy = conn.with_proxy(&dest, "/org/mpris/MediaPlayer2", Duration::from_millis(2000));
+        if let Err(e) = proxy.method_call::<(), _, _, _>("org.mpris.MediaPlayer2.Player", "Play", ()) {
+            error!("Failed to resume {}: {}", dest, e);
+        } else {
+            debug!("Resumed {} after ear-detection in-ear", dest);
+        }
+    }
+}
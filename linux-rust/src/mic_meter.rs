@@ -0,0 +1,81 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use log::error;
+
+const EMA_ALPHA: f32 = 0.2;
+
+static LEVEL: OnceLock<Arc<Mutex<f32>>> = OnceLock::new();
+
+fn level_handle() -> Arc<Mutex<f32>> {
+    LEVEL.get_or_init(|| {
+        let level = Arc::new(Mutex::new(0.0f32));
+        let level_clone = level.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = run_input_stream(level_clone) {
+                error!("Failed to start microphone level meter: {}", e);
+            }
+        });
+        level
+    }).clone()
+}
+
+fn run_input_stream(level: Arc<Mutex<f32>>) -> Result<(), Box<dyn std::error::Error>> {
+    let host = cpal::default_host();
+    let device = host.default_input_device().ok_or("no default input device")?;
+    let config = device.default_input_config()?;
+
+    let err_fn = |err| error!("Microphone input stream error: {}", err);
+
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config.into(),
+            move |data: &[f32], _| update_level(&level, data.iter().map(|s| *s)),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[i16], _| update_level(&level, data.iter().map(|s| *s as f32 / i16::MAX as f32)),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::U16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[u16], _| update_level(&level, data.iter().map(|s| *s as f32 / u16::MAX as f32 * 2.0 - 1.0)),
+            err_fn,
+            None,
+        )?,
+        sample_format => return Err(format!("unsupported sample format: {:?}", sample_format).into()),
+    };
+
+    stream.play()?;
+    // Keep this thread (and the stream, which must stay alive on the thread
+    // it was created on) parked for the lifetime of the process.
+    loop {
+        std::thread::park();
+    }
+}
+
+fn update_level(level: &Mutex<f32>, samples: impl Iterator<Item = f32>) {
+    let mut sum_sq = 0.0f32;
+    let mut count = 0usize;
+    for s in samples {
+        sum_sq += s * s;
+        count += 1;
+    }
+    if count == 0 {
+        return;
+    }
+    let rms = (sum_sq / count as f32).sqrt();
+    let mut current = level.lock().unwrap();
+    *current = *current * (1.0 - EMA_ALPHA) + rms * EMA_ALPHA;
+}
+
+/// Returns the current smoothed microphone RMS level in `[0.0, 1.0]`,
+/// starting the monitoring thread on first use. Cheap enough to poll from an
+/// `iced` subscription tick; the audio callback itself never takes a lock
+/// beyond a quick swap of the smoothed value.
+pub fn current_level() -> f32 {
+    *level_handle().lock().unwrap()
+}
@@ -0,0 +1,159 @@
+use std::sync::{OnceLock, RwLock};
+use std::time::SystemTime;
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+
+use crate::ui::tray::TrayIconMode;
+use crate::utils::{get_app_settings_path, MyTheme};
+
+/// Typed, single source of truth for user preferences. Replaces the old
+/// pattern of re-reading and re-parsing the settings JSON on every call site
+/// (most notably the hot tray-render path).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub theme: MyTheme,
+    #[serde(default)]
+    pub tray_icon_mode: TrayIconMode,
+    /// Minimum smoothed mic RMS level (`[0.0, 1.0]`) considered "picking up
+    /// your voice" for the Conversation Awareness level meter.
+    #[serde(default = "default_mic_sensitivity_threshold")]
+    pub mic_sensitivity_threshold: f32,
+    /// Opt-in: switch the default audio sink/profile to the AirPods on
+    /// connect and restore the previous sink on disconnect.
+    #[serde(default)]
+    pub audio_auto_switch_enabled: bool,
+    /// Opt-in: print a waybar/i3status "custom module" JSON frame per
+    /// connected device to stdout on every state change.
+    #[serde(default)]
+    pub status_export_stdout: bool,
+    /// Opt-in: also serve the same frames over a Unix domain socket at this
+    /// path, so a bar can connect instead of spawning us as its `exec`.
+    #[serde(default)]
+    pub status_export_socket_path: Option<String>,
+    /// Opt-in: publish per-device battery/charging/noise-control state as
+    /// D-Bus properties under the `librepods` well-known name, emitting
+    /// `PropertiesChanged` so a bar can subscribe instead of polling.
+    #[serde(default)]
+    pub status_export_dbus: bool,
+    /// Opt-in: request the `org.librepods.Device` system-bus name and expose
+    /// `SetListeningMode`/`SetConversationDetect` control methods for each
+    /// connected device.
+    #[serde(default)]
+    pub device_bus_enabled: bool,
+    /// Opt-in: pause/resume the active media player when both earbuds are
+    /// taken out/put back in.
+    #[serde(default)]
+    pub ear_detection_auto_pause_enabled: bool,
+    /// Placeholder template for the tray tooltip text. Supports
+    /// `{device_name}`, `{left_battery}`, `{right_battery}`,
+    /// `{case_battery}`, `{anc_mode}`, and a conditional `{charging?text}`.
+    #[serde(default = "default_tray_text_template")]
+    pub tray_text_template: String,
+}
+
+fn default_tray_text_template() -> String {
+    "{device_name} {left_battery}/{right_battery}".to_string()
+}
+
+fn default_mic_sensitivity_threshold() -> f32 {
+    0.1
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: MyTheme::Dark,
+            tray_icon_mode: TrayIconMode::Ring,
+            mic_sensitivity_threshold: default_mic_sensitivity_threshold(),
+            audio_auto_switch_enabled: false,
+            status_export_stdout: false,
+            status_export_socket_path: None,
+            status_export_dbus: false,
+            device_bus_enabled: false,
+            ear_detection_auto_pause_enabled: false,
+            tray_text_template: default_tray_text_template(),
+        }
+    }
+}
+
+struct Cache {
+    settings: Settings,
+    mtime: Option<SystemTime>,
+}
+
+static CACHE: OnceLock<RwLock<Cache>> = OnceLock::new();
+
+fn cache() -> &'static RwLock<Cache> {
+    CACHE.get_or_init(|| RwLock::new(Cache {
+        settings: load_from_disk(),
+        mtime: current_mtime(),
+    }))
+}
+
+fn current_mtime() -> Option<SystemTime> {
+    std::fs::metadata(get_app_settings_path()).ok().and_then(|m| m.modified().ok())
+}
+
+fn load_from_disk() -> Settings {
+    let path = get_app_settings_path();
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return Settings::default();
+    };
+    let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&contents) else {
+        error!("Failed to parse settings file at {}, using defaults", path.display());
+        return Settings::default();
+    };
+    // Migrate the old `tray_text_mode` bool (false = ring, true = text) to
+    // the newer three-way `tray_icon_mode` enum if it hasn't been saved yet.
+    if value.get("tray_icon_mode").is_none() {
+        if let Some(legacy) = value.get("tray_text_mode").and_then(|v| v.as_bool()) {
+            value["tray_icon_mode"] = serde_json::json!(if legacy { "text" } else { "ring" });
+        }
+    }
+    serde_json::from_value(value).unwrap_or_else(|e| {
+        error!("Failed to deserialize settings, using defaults: {}", e);
+        Settings::default()
+    })
+}
+
+/// Returns the current settings. Reads from the in-memory cache unless the
+/// settings file's mtime has changed since the last load (e.g. an external
+/// edit), in which case it transparently reloads.
+pub fn load_settings() -> Settings {
+    let lock = cache();
+    let mtime_now = current_mtime();
+    {
+        let guard = lock.read().unwrap();
+        if guard.mtime == mtime_now {
+            return guard.settings.clone();
+        }
+    }
+    let mut guard = lock.write().unwrap();
+    debug!("Settings file changed on disk, reloading");
+    guard.settings = load_from_disk();
+    guard.mtime = mtime_now;
+    guard.settings.clone()
+}
+
+/// Persists `settings` to disk (via a write-then-rename so readers never see
+/// a partially written file) and updates the in-memory cache immediately.
+pub fn save_settings(settings: Settings) {
+    let path = get_app_settings_path();
+    let json = match serde_json::to_string(&settings) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Failed to serialize settings: {}", e);
+            return;
+        }
+    };
+    let tmp_path = path.with_extension("json.tmp");
+    if let Err(e) = std::fs::write(&tmp_path, &json).and_then(|_| std::fs::rename(&tmp_path, &path)) {
+        error!("Failed to write settings to {}: {}", path.display(), e);
+        return;
+    }
+    let mut guard = cache().write().unwrap();
+    guard.mtime = current_mtime();
+    guard.settings = settings;
+}
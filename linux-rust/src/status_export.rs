@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use dbus::arg::{RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::channel::Sender;
+use dbus::message::{Message, MatchRule};
+use log::{debug, error};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::bluetooth::aacp::BatteryStatus;
+use crate::devices::enums::DeviceState;
+use crate::settings::load_settings;
+
+const DBUS_BUS_NAME: &str = "org.librepods.Status";
+const DBUS_OBJECT_PATH: &str = "/org/librepods/Status";
+const DBUS_INTERFACE: &str = "org.librepods.Status1";
+
+/// One frame in the shape waybar/i3status-rs/i3blocks expect from a `custom`
+/// module: https://github.com/Alexays/Waybar/wiki/Module:-Custom
+#[derive(Debug, Clone, Serialize)]
+struct StatusFrame {
+    text: String,
+    tooltip: String,
+    class: String,
+    percentage: Option<u8>,
+}
+
+/// One block in the shape i3bar expects from a status command:
+/// https://i3wm.org/docs/i3bar-protocol.html
+#[derive(Debug, Clone, Serialize)]
+struct I3barBlock {
+    full_text: String,
+    short_text: String,
+    state: String,
+}
+
+static CLIENTS: OnceLock<AsyncMutex<Vec<UnixStream>>> = OnceLock::new();
+
+fn clients() -> &'static AsyncMutex<Vec<UnixStream>> {
+    CLIENTS.get_or_init(|| AsyncMutex::new(Vec::new()))
+}
+
+static I3BAR_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Switches `publish()` into `--i3bar` mode, where it additionally prints one
+/// i3bar-protocol JSON block per device to stdout. Set once at startup from
+/// the `--i3bar` CLI flag.
+pub fn set_i3bar_mode(enabled: bool) {
+    I3BAR_MODE.store(enabled, Ordering::Relaxed);
+}
+
+static DBUS_CONN: OnceLock<Connection> = OnceLock::new();
+static LATEST_FRAMES: OnceLock<std::sync::Mutex<Vec<StatusFrame>>> = OnceLock::new();
+
+fn latest_frames() -> &'static std::sync::Mutex<Vec<StatusFrame>> {
+    LATEST_FRAMES.get_or_init(|| std::sync::Mutex::new(Vec::new()))
+}
+
+/// Requests the `org.librepods.Status` bus name on the session bus and
+/// starts answering `org.freedesktop.DBus.Properties` calls for it. Safe to
+/// call more than once; only the first call actually starts the service.
+/// The latest frames are also pushed out as `PropertiesChanged` signals from
+/// `publish()`, so a bar doesn't have to poll `Get`/`GetAll`.
+pub fn start_dbus_service() {
+    if DBUS_CONN.get().is_some() {
+        return;
+    }
+
+    let conn = match Connection::new_session() {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to connect to session bus for D-Bus status export: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = conn.request_name(DBUS_BUS_NAME, false, true, false) {
+        error!("Failed to request D-Bus name {}: {}", DBUS_BUS_NAME, e);
+        return;
+    }
+
+    conn.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(|msg, conn| {
+            handle_properties_call(&msg, conn);
+            true
+        }),
+    );
+
+    if DBUS_CONN.set(conn).is_err() {
+        return;
+    }
+
+    std::thread::spawn(|| {
+        let conn = DBUS_CONN.get().expect("D-Bus connection initialized above");
+        loop {
+            if let Err(e) = conn.process(std::time::Duration::from_millis(200)) {
+                error!("D-Bus status export connection error: {}", e);
+                break;
+            }
+        }
+    });
+    debug!("D-Bus status export service listening as {}", DBUS_BUS_NAME);
+}
+
+fn handle_properties_call(msg: &Message, conn: &Connection) {
+    if msg.interface().as_deref() != Some("org.freedesktop.DBus.Properties") {
+        return;
+    }
+    if msg.path().as_deref() != Some(DBUS_OBJECT_PATH) {
+        return;
+    }
+
+    let devices_json = serde_json::to_string(&*latest_frames().lock().unwrap()).unwrap_or_default();
+    let reply = match msg.member().as_deref() {
+        Some("GetAll") => {
+            let mut props: HashMap<String, Variant<Box<dyn RefArg>>> = HashMap::new();
+            props.insert("Devices".to_string(), Variant(Box::new(devices_json) as Box<dyn RefArg>));
+            msg.method_return().append1(props)
+        }
+        Some("Get") => msg.method_return().append1(Variant(devices_json)),
+        _ => return,
+    };
+    let _ = conn.channel().send(reply);
+}
+
+fn emit_dbus_properties_changed(frames: &[StatusFrame]) {
+    let Some(conn) = DBUS_CONN.get() else { return };
+
+    *latest_frames().lock().unwrap() = frames.to_vec();
+    let devices_json = serde_json::to_string(frames).unwrap_or_default();
+
+    let mut changed: HashMap<String, Variant<Box<dyn RefArg>>> = HashMap::new();
+    changed.insert("Devices".to_string(), Variant(Box::new(devices_json) as Box<dyn RefArg>));
+
+    let Some(signal) = Message::new_signal(DBUS_OBJECT_PATH, "org.freedesktop.DBus.Properties", "PropertiesChanged") else { return };
+    let signal = signal.append3(DBUS_INTERFACE, changed, Vec::<String>::new());
+    let _ = conn.channel().send(signal);
+}
+
+/// Starts the D-Bus status service if `status_export_dbus` is set, and
+/// accepts connections on `status_export_socket_path` if that is configured.
+/// Safe to call once at startup; a no-op when neither is set.
+pub fn start() {
+    let settings = load_settings();
+
+    if settings.status_export_dbus {
+        start_dbus_service();
+    }
+
+    let Some(path) = settings.status_export_socket_path else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let _ = std::fs::remove_file(&path);
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind status export socket {}: {}", path, e);
+                return;
+            }
+        };
+        debug!("Status export socket listening at {}", path);
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => clients().lock().await.push(stream),
+                Err(e) => {
+                    error!("Status export socket accept failed: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Publishes one frame per device in `device_states` to stdout (if enabled)
+/// and to every client currently connected to the status export socket.
+/// Called synchronously from `update()` whenever `device_states` changes, so
+/// it never blocks on a slow/dead socket -- the actual socket writes happen
+/// in a spawned task.
+pub fn publish(device_states: &HashMap<String, DeviceState>) {
+    let settings = load_settings();
+    let i3bar_mode = I3BAR_MODE.load(Ordering::Relaxed);
+    if !settings.status_export_stdout
+        && settings.status_export_socket_path.is_none()
+        && !settings.status_export_dbus
+        && !i3bar_mode
+    {
+        return;
+    }
+
+    let frames: Vec<StatusFrame> = device_states.values().map(frame_for).collect();
+
+    if settings.status_export_stdout {
+        for frame in &frames {
+            match serde_json::to_string(frame) {
+                Ok(line) => println!("{}", line),
+                Err(e) => error!("Failed to serialize status frame: {}", e),
+            }
+        }
+    }
+
+    if i3bar_mode {
+        for frame in &frames {
+            let block = I3barBlock {
+                full_text: frame.text.clone(),
+                short_text: frame.percentage.map(|p| format!("{}%", p)).unwrap_or_default(),
+                state: if frame.class.ends_with("-charging") { "Good".to_string() } else { "Idle".to_string() },
+            };
+            match serde_json::to_string(&block) {
+                Ok(line) => println!("{}", line),
+                Err(e) => error!("Failed to serialize i3bar block: {}", e),
+            }
+        }
+    }
+
+    if settings.status_export_dbus {
+        emit_dbus_properties_changed(&frames);
+    }
+
+    if settings.status_export_socket_path.is_some() {
+        tokio::spawn(async move {
+            let mut guard = clients().lock().await;
+            let mut alive = Vec::with_capacity(guard.len());
+            for mut stream in guard.drain(..) {
+                let mut ok = true;
+                for frame in &frames {
+                    let Ok(mut line) = serde_json::to_string(frame) else { continue };
+                    line.push('\n');
+                    if stream.write_all(line.as_bytes()).await.is_err() {
+                        ok = false;
+                        break;
+                    }
+                }
+                if ok {
+                    alive.push(stream);
+                }
+            }
+            *guard = alive;
+        });
+    }
+}
+
+fn frame_for(state: &DeviceState) -> StatusFrame {
+    match state {
+        DeviceState::AirPods(s) => {
+            let level_for = |component_mask: u8| {
+                s.battery.iter().find(|b| b.component as u8 == component_mask).map(|b| b.level)
+            };
+            let left = level_for(0x04);
+            let right = level_for(0x02);
+            let case = level_for(0x08);
+            let min_pct = [left, right].into_iter().flatten().min();
+            let charging = s.battery.iter().any(|b| b.status == BatteryStatus::Charging);
+
+            StatusFrame {
+                text: format!("{} {}", s.device_name, format_pct(min_pct)),
+                tooltip: format!(
+                    "{}\nL {}  R {}  Case {}\n{}",
+                    s.device_name, format_pct(left), format_pct(right), format_pct(case), s.noise_control_mode
+                ),
+                class: if charging { "airpods-charging".to_string() } else { "airpods".to_string() },
+                percentage: min_pct,
+            }
+        }
+        DeviceState::Nothing(s) => StatusFrame {
+            text: format!("Nothing Ear {}", s.anc_mode),
+            tooltip: format!("Noise Control: {}", s.anc_mode),
+            class: "nothing".to_string(),
+            percentage: None,
+        },
+        DeviceState::GenericBle(s) => {
+            let level = s.battery.first().map(|b| b.level);
+            let charging = s.battery.iter().any(|b| b.status == BatteryStatus::Charging);
+            StatusFrame {
+                text: format!("BLE Device {}", format_pct(level)),
+                tooltip: format!("Battery: {}", format_pct(level)),
+                class: if charging { "generic-ble-charging".to_string() } else { "generic-ble".to_string() },
+                percentage: level,
+            }
+        }
+    }
+}
+
+fn format_pct(pct: Option<u8>) -> String {
+    pct.map(|p| format!("{}%", p)).unwrap_or_else(|| "--".to_string())
+}
@@ -4,12 +4,16 @@ use iced::{Background, Border, Color, Length, Theme};
 use iced::widget::button::Style;
 use log::error;
 use crate::devices::enums::{AirPodsState, DeviceData, DeviceInformation};
+use crate::ui::messages::BluetoothUIMessage;
 use crate::ui::window::{DeviceMessage, Message};
 
 pub fn airpods_view<'a>(
     mac: &str,
     devices_list: &HashMap<String, DeviceData>,
     state: &AirPodsState,
+    connected: bool,
+    mic_level: f32,
+    mic_sensitivity_threshold: f32,
 ) -> iced::widget::Container<'a, Message> {
     let mut information_col = column![];
     let mac = mac.to_string();
@@ -173,10 +177,77 @@ pub fn airpods_view<'a>(
         .label("Conversation Awareness")
         .on_toggle(move |is_enabled| Message::DeviceMessage(mac.to_string(), DeviceMessage::ConversationAwarenessToggled(is_enabled)));
 
+    let ear_detection_mac = mac.to_string();
+    let ear_detection_toggler = toggler(state.ear_detection_auto_pause_enabled)
+        .label("Pause media when removed from ear")
+        .on_toggle(move |is_enabled| Message::DeviceMessage(ear_detection_mac.to_string(), DeviceMessage::EarDetectionAutoPauseToggled(is_enabled)));
+
+    let connected_mac = mac.to_string();
+    let connected_toggler = toggler(connected)
+        .label("Connected")
+        .on_toggle(move |should_be_connected| {
+            Message::BluetoothMessage(BluetoothUIMessage::SetConnected(connected_mac.to_string(), should_be_connected))
+        });
+
+    let auto_reconnect_enabled = devices_list.get(mac.as_str()).is_some_and(|d| d.auto_reconnect);
+    let auto_reconnect_mac = mac.to_string();
+    let auto_reconnect_toggler = toggler(auto_reconnect_enabled)
+        .label("Auto-reconnect when dropped")
+        .on_toggle(move |enabled| {
+            Message::BluetoothMessage(BluetoothUIMessage::SetAutoReconnect(auto_reconnect_mac.to_string(), enabled))
+        });
+
+    let mic_level_bar = {
+        let level = mic_level.clamp(0.0, 1.0);
+        let bar_width = 200.0;
+        let filled_width = (level * bar_width).max(1.0);
+        let above_threshold = level >= mic_sensitivity_threshold;
+        container(
+            row![
+                container(Space::with_width(Length::Fixed(filled_width)))
+                    .height(Length::Fixed(10.0))
+                    .style(move |_theme: &Theme| {
+                        let mut style = container::Style::default();
+                        style.background = Some(Background::Color(if above_threshold {
+                            Color::from_rgb(0.2, 0.8, 0.2)
+                        } else {
+                            Color::from_rgb(0.6, 0.6, 0.6)
+                        }));
+                        style
+                    }),
+                Space::with_width(Length::Fill),
+            ]
+        )
+            .width(Length::Fixed(bar_width))
+            .height(Length::Fixed(10.0))
+            .style(|theme: &Theme| {
+                let mut style = container::Style::default();
+                style.background = Some(Background::Color(theme.palette().primary.scale_alpha(0.1)));
+                let mut border = Border::default();
+                border.color = theme.palette().text.scale_alpha(0.3);
+                style.border = border.rounded(4);
+                style
+            })
+    };
+
+    let mic_level_col = column![
+        text("Conversation Awareness Mic Level").size(14),
+        Space::with_height(Length::from(4)),
+        mic_level_bar,
+    ];
+
     container(
         column![
+            connected_toggler,
+            Space::with_height(Length::from(10)),
             toggler_widget,
             Space::with_height(Length::from(10)),
+            ear_detection_toggler,
+            Space::with_height(Length::from(10)),
+            auto_reconnect_toggler,
+            Space::with_height(Length::from(10)),
+            mic_level_col,
+            Space::with_height(Length::from(10)),
             container(information_col)
                 .style(
                     |theme: &Theme| {
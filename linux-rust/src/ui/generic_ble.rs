@@ -0,0 +1,34 @@
+use iced::widget::{button, column, container, text, Space};
+use iced::{Length, Theme};
+use crate::devices::enums::GenericBleState;
+use crate::ui::messages::BluetoothUIMessage;
+use crate::ui::window::Message;
+
+pub fn generic_ble_view<'a>(mac: &str, state: &GenericBleState) -> iced::widget::Container<'a, Message> {
+    let level = state.battery.first().map(|b| b.level);
+    let battery_text = match level {
+        Some(level) => format!("\u{F0079} {}%", level),
+        None => "Battery level unknown".to_string(),
+    };
+
+    let mac = mac.to_string();
+    let disconnect_button = button(text("Disconnect"))
+        .on_press(Message::BluetoothMessage(BluetoothUIMessage::SetConnected(mac, false)));
+
+    container(
+        column![
+            text(battery_text).size(18).style(
+                |theme: &Theme| {
+                    let mut style = text::Style::default();
+                    style.color = Some(theme.palette().text);
+                    style
+                }
+            ),
+            Space::with_height(Length::from(10)),
+            disconnect_button,
+        ]
+    )
+        .padding(20)
+        .center_x(Length::Fill)
+        .height(Length::Fill)
+}
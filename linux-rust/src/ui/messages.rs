@@ -1,4 +1,4 @@
-use crate::bluetooth::aacp::{AACPEvent, ControlCommandIdentifiers};
+use crate::bluetooth::aacp::{AACPEvent, BatteryComponent, ControlCommandIdentifiers};
 use crate::devices::enums::NothingAncMode;
 
 #[derive(Debug, Clone)]
@@ -8,6 +8,11 @@ pub enum BluetoothUIMessage {
     DeviceDisconnected(String), // mac
     AACPUIEvent(String, AACPEvent), // mac, event
     ATTNotification(String, u16, Vec<u8>), // mac, handle, data
+    EarDetectionChanged(String, bool, bool), // mac, left_in_ear, right_in_ear
+    SetConnected(String, bool), // mac, should_be_connected
+    SetAutoReconnect(String, bool), // mac, enabled
+    ReconnectionStatusChanged(String, bool), // mac, is_reconnecting
+    BatteryLevelChanged(String, Vec<BatteryComponent>), // mac, battery
     NoOp
 }
 
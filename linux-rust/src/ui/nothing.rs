@@ -1,13 +1,14 @@
 use std::collections::HashMap;
 use iced::{Background, Border, Length, Theme};
-use iced::widget::{container, text, column, row, Space, combo_box};
-use crate::devices::enums::{DeviceData, DeviceInformation, NothingState};
+use iced::widget::{container, text, column, row, toggler, Space, combo_box};
+use crate::devices::enums::{DeviceData, DeviceInformation, NothingAncMode, NothingState};
+use crate::ui::messages::BluetoothUIMessage;
 use crate::ui::window::Message;
 
 pub fn nothing_view<'a>(
     mac: &str,
     devices_list: &HashMap<String, DeviceData>,
-    state: &NothingState
+    state: &NothingState,
 ) -> iced::widget::Container<'a, Message> {
     let mut information_col = iced::widget::column![];
     let mac = mac.to_string();
@@ -50,13 +51,32 @@ pub fn nothing_view<'a>(
                 );
         }
     }
+    let mac_for_combo = mac.clone();
+    let noise_control_combo = combo_box(
+        &state.anc_mode_state,
+        "Noise Control Mode",
+        Some(&state.anc_mode),
+        move |mode: NothingAncMode| Message::SetNoiseControl(mac_for_combo.clone(), mode),
+    );
+
+    let auto_reconnect_enabled = devices_list.get(mac.as_str()).is_some_and(|d| d.auto_reconnect);
+    let auto_reconnect_mac = mac.clone();
+    let auto_reconnect_toggler = toggler(auto_reconnect_enabled)
+        .label("Auto-reconnect when dropped")
+        .on_toggle(move |enabled| {
+            Message::BluetoothMessage(BluetoothUIMessage::SetAutoReconnect(auto_reconnect_mac.clone(), enabled))
+        });
+
     container(
         column![
             row![
                 text("Noise Control Mode").size(18),
                 Space::with_width(Length::Fill),
-            //     combobox here
+                noise_control_combo,
             ],
+            Space::with_height(Length::from(10)),
+            auto_reconnect_toggler,
+            Space::with_height(Length::from(10)),
             container(information_col)
                 .style(
                     |theme: &Theme| {
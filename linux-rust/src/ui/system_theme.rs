@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use dbus::arg::{RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use log::{debug, warn};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// freedesktop desktop portal's appearance settings, as described at
+/// https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html
+const PORTAL_DEST: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_SETTINGS_IFACE: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+/// The portal reports `1` for "prefer dark" and `2` for "prefer light" (`0`
+/// means no preference); anything else is treated as no preference.
+fn color_scheme_prefers_dark(value: u64) -> Option<bool> {
+    match value {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    }
+}
+
+/// Reads the current desktop color-scheme preference via the portal's
+/// `Settings.Read` method. Returns `None` if the portal isn't reachable
+/// (e.g. no desktop portal running) or the value isn't one we recognize,
+/// so callers can fall back to a fixed theme instead of erroring.
+pub fn read_color_scheme_prefers_dark() -> Option<bool> {
+    let conn = Connection::new_session().ok()?;
+    let proxy = conn.with_proxy(PORTAL_DEST, PORTAL_PATH, Duration::from_millis(2000));
+    let (value,): (Variant<Box<dyn RefArg>>,) = proxy
+        .method_call(PORTAL_SETTINGS_IFACE, "Read", (APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY))
+        .ok()?;
+    let scheme = value.0.as_u64().or_else(|| value.0.as_i64().map(|v| v as u64))?;
+    color_scheme_prefers_dark(scheme)
+}
+
+/// Spawns a dedicated thread that blocks on the desktop portal's
+/// `SettingChanged` signal for `org.freedesktop.appearance`/`color-scheme`
+/// and forwards the new preference (`true` = dark, `false` = light) over
+/// `tx` whenever the user flips their desktop's appearance -- mirrors the
+/// `PropertiesChanged` listener thread in `main.rs`.
+pub fn spawn_listener(tx: UnboundedSender<bool>) {
+    std::thread::spawn(move || {
+        let Ok(conn) = Connection::new_session() else {
+            warn!("Failed to connect to the session bus for portal theme-change notifications");
+            return;
+        };
+
+        let rule = MatchRule::new_signal(PORTAL_SETTINGS_IFACE, "SettingChanged");
+        let add_match_result = conn.add_match(rule, move |_: (), _, msg| {
+            let Ok((namespace, key, value)) = msg.read3::<String, String, Variant<Box<dyn RefArg>>>() else {
+                return true;
+            };
+            if namespace != APPEARANCE_NAMESPACE || key != COLOR_SCHEME_KEY {
+                return true;
+            }
+            let Some(scheme) = value.0.as_u64().or_else(|| value.0.as_i64().map(|v| v as u64)) else {
+                return true;
+            };
+            if let Some(prefers_dark) = color_scheme_prefers_dark(scheme) {
+                let _ = tx.send(prefers_dark);
+            }
+            true
+        });
+        if let Err(e) = add_match_result {
+            warn!("Failed to subscribe to the portal's SettingChanged signal: {}", e);
+            return;
+        }
+
+        debug!("Listening for desktop color-scheme changes via the settings portal...");
+        loop {
+            if let Err(e) = conn.process(Duration::from_millis(1000)) {
+                warn!("Portal theme-change listener D-Bus error: {}", e);
+                break;
+            }
+        }
+    });
+}
@@ -2,11 +2,35 @@
 
 use ab_glyph::{Font, ScaleFont};
 use ksni::{Icon, ToolTip};
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::bluetooth::aacp::{BatteryStatus, ControlCommandIdentifiers};
+use crate::devices::config;
+use crate::devices::enums::{AirPodsNoiseControlMode, DeviceType};
+use crate::bluetooth::le::ProximityState;
 use crate::ui::messages::BluetoothUIMessage;
-use crate::utils::get_app_settings_path;
+
+/// How the tray icon renders battery state. `tray_text_mode`'s old bool
+/// (`false` = ring, `true` = text) is still honored as a fallback for users
+/// who haven't re-saved settings since upgrading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayIconMode {
+    Ring,
+    Text,
+    Segments,
+}
+
+impl std::fmt::Display for TrayIconMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrayIconMode::Ring => write!(f, "Ring"),
+            TrayIconMode::Text => write!(f, "Text"),
+            TrayIconMode::Segments => write!(f, "Segments"),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct MyTray {
@@ -20,6 +44,9 @@ pub struct MyTray {
     pub battery_c: Option<u8>,
     pub battery_c_status: Option<BatteryStatus>,
     pub connected: bool,
+    pub mac_address: Option<String>,
+    pub device_name: Option<String>,
+    pub proximity: Option<ProximityState>,
     pub listening_mode: Option<u8>,
     pub allow_off_option: Option<u8>,
     pub command_tx: Option<UnboundedSender<(ControlCommandIdentifiers, Vec<u8>)>>,
@@ -66,42 +93,26 @@ impl ksni::Tray for MyTray {
         };
         let any_bud_charging = matches!(self.battery_l_status, Some(BatteryStatus::Charging))
             || matches!(self.battery_r_status, Some(BatteryStatus::Charging));
-        let app_settings_path = get_app_settings_path();
-        let settings = std::fs::read_to_string(&app_settings_path)
-            .ok()
-            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
-        let text_mode = settings.clone()
-            .and_then(|v| v.get("tray_text_mode").cloned())
-            .and_then(|ttm| serde_json::from_value(ttm).ok())
-            .unwrap_or(false);
-        let icon = generate_icon(&text, text_mode, any_bud_charging);
+        let mode = crate::settings::load_settings().tray_icon_mode;
+        let icon = generate_icon(&text, mode, any_bud_charging, self);
         vec![icon]
     }
     fn tool_tip(&self) -> ToolTip {
-        let format_component = |label: &str, level: Option<u8>, status: Option<BatteryStatus>| -> String {
-            match status {
-                Some(BatteryStatus::Disconnected) => format!("{}: -", label),
-                _ => {
-                    let pct = level.map(|b| format!("{}%", b)).unwrap_or("?".to_string());
-                    let suffix = if status == Some(BatteryStatus::Charging) {
-                        "⚡"
-                    } else {
-                        ""
-                    };
-                    format!("{}: {}{}", label, pct, suffix)
-                }
-            }
+        let proximity = match self.proximity {
+            Some(ProximityState::Near) => " · Near",
+            Some(ProximityState::Far) => " · Far",
+            Some(ProximityState::Lost) => " · Out of range",
+            None => "",
         };
 
-        let l = format_component("L", self.battery_l, self.battery_l_status);
-        let r = format_component("R", self.battery_r, self.battery_r_status);
-        let c = format_component("C", self.battery_c, self.battery_c_status);
+        let template = crate::settings::load_settings().tray_text_template;
+        let description = format!("{}{}", render_tray_template(&template, self), proximity);
 
         ToolTip {
             icon_name: "".to_string(),
             icon_pixmap: vec![],
             title: "Battery Status".to_string(),
-            description: format!("{} {} {}", l, r, c),
+            description,
         }
     }
     fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
@@ -125,7 +136,7 @@ impl ksni::Tray for MyTray {
             options.iter().position(|&(_, val)| val == mode)
         }).unwrap_or(0);
         let options_clone = options.clone();
-        vec![
+        let mut items: Vec<ksni::MenuItem<Self>> = vec![
             StandardItem {
                 label: "Open Window".into(),
                 icon_name: "window-new".into(),
@@ -152,6 +163,29 @@ impl ksni::Tray for MyTray {
             }
             .into(),
             MenuItem::Separator,
+            StandardItem {
+                label: match self.proximity {
+                    Some(ProximityState::Near) => "Proximity: Near".into(),
+                    Some(ProximityState::Far) => "Proximity: Far".into(),
+                    Some(ProximityState::Lost) => "Proximity: Out of range".into(),
+                    None => "Proximity: Unknown".into(),
+                },
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: "Connected".into(),
+                checked: self.connected,
+                enabled: self.mac_address.is_some(),
+                activate: Box::new(|this: &mut Self| {
+                    if let (Some(tx), Some(mac)) = (&this.ui_tx, &this.mac_address) {
+                        let _ = tx.send(BluetoothUIMessage::SetConnected(mac.clone(), !this.connected));
+                    }
+                }),
+                ..Default::default()
+            }
+            .into(),
             CheckmarkItem {
                 label: "Conversation Detection".into(),
                 checked: self.conversation_detect_enabled.unwrap_or(false),
@@ -169,18 +203,176 @@ impl ksni::Tray for MyTray {
                 ..Default::default()
             }
             .into(),
-            StandardItem {
-                label: "Exit".into(),
-                icon_name: "application-exit".into(),
-                activate: Box::new(|_| std::process::exit(0)),
+        ];
+
+        items.push(MenuItem::Separator);
+        items.extend(devices_submenu(self));
+
+        items.push(StandardItem {
+            label: "Exit".into(),
+            icon_name: "application-exit".into(),
+            activate: Box::new(|_| std::process::exit(0)),
+            ..Default::default()
+        }
+        .into());
+        items
+    }
+}
+
+/// Builds one menu entry per device in `devices.json`, each a submenu with a
+/// Connect/Disconnect toggle and -- for the device this tray instance is
+/// actively tracking -- the same noise-control options as the `RadioGroup`
+/// above, so switching modes doesn't require opening the window first.
+/// Other AirPods/Nothing entries only get Connect/Disconnect for now, since
+/// this tray only holds a live command channel for the one currently-active
+/// device.
+fn devices_submenu(tray: &MyTray) -> Vec<ksni::MenuItem<MyTray>> {
+    use ksni::menu::*;
+
+    let config = config::load();
+
+    let mut macs: Vec<String> = config.iter().map(|(mac, _group, _entry)| mac.to_string()).collect();
+    if macs.is_empty() {
+        return vec![];
+    }
+    macs.sort();
+
+    let mut items = Vec::with_capacity(macs.len() + 1);
+    items.push(
+        StandardItem {
+            label: "Devices".into(),
+            enabled: false,
+            ..Default::default()
+        }
+        .into(),
+    );
+
+    for mac in macs {
+        let Some(entry) = config.get(&mac) else { continue };
+        let is_active = tray.mac_address.as_deref() == Some(mac.as_str());
+        let is_connected = is_active && tray.connected;
+        let label = format!("{} ({})", entry.display_name(), if is_connected { "Connected" } else { "Disconnected" });
+
+        let mut submenu: Vec<ksni::MenuItem<MyTray>> = vec![CheckmarkItem {
+            label: if is_connected { "Disconnect".into() } else { "Connect".into() },
+            checked: is_connected,
+            activate: Box::new({
+                let mac = mac.clone();
+                move |this: &mut MyTray| {
+                    if let Some(tx) = &this.ui_tx {
+                        let should_connect = !(this.mac_address.as_deref() == Some(mac.as_str()) && this.connected);
+                        let _ = tx.send(BluetoothUIMessage::SetConnected(mac.clone(), should_connect));
+                    }
+                }
+            }),
+            ..Default::default()
+        }
+        .into()];
+
+        if entry.data.type_ == DeviceType::AirPods && is_active {
+            let allow_off = tray.allow_off_option == Some(0x01);
+            let options = if allow_off {
+                vec![
+                    ("Off", 0x01),
+                    ("Noise Cancellation", 0x02),
+                    ("Transparency", 0x03),
+                    ("Adaptive", 0x04),
+                ]
+            } else {
+                vec![
+                    ("Noise Cancellation", 0x02),
+                    ("Transparency", 0x03),
+                    ("Adaptive", 0x04),
+                ]
+            };
+            let selected = tray.listening_mode.and_then(|mode| {
+                options.iter().position(|&(_, val)| val == mode)
+            }).unwrap_or(0);
+            let options_clone = options.clone();
+            submenu.push(MenuItem::Separator);
+            submenu.push(
+                RadioGroup {
+                    selected,
+                    select: Box::new(move |this: &mut MyTray, current| {
+                        if let Some(tx) = &this.command_tx {
+                            let value = options_clone.get(current).map(|&(_, val)| val).unwrap_or(0x02);
+                            let _ = tx.send((ControlCommandIdentifiers::ListeningMode, vec![value]));
+                        }
+                    }),
+                    options: options.into_iter().map(|(label, _)| RadioItem {
+                        label: label.into(),
+                        ..Default::default()
+                    }).collect(),
+                    ..Default::default()
+                }
+                .into(),
+            );
+        }
+
+        items.push(
+            SubMenu {
+                label,
+                submenu,
                 ..Default::default()
             }
             .into(),
-        ]
+        );
     }
+
+    items
 }
 
-fn generate_icon(text: &str, text_mode: bool, charging: bool) -> Icon {
+/// Fills in the user-configurable `tray_text_template` setting against the
+/// current tray state. Supported placeholders: `{device_name}`,
+/// `{left_battery}`, `{right_battery}`, `{case_battery}`, `{anc_mode}`, and a
+/// conditional `{charging?text}` that expands to `text` only while a bud is
+/// charging.
+fn render_tray_template(template: &str, tray: &MyTray) -> String {
+    let battery_str = |level: Option<u8>, status: Option<BatteryStatus>| -> String {
+        if status == Some(BatteryStatus::Disconnected) {
+            "-".to_string()
+        } else {
+            level.map(|b| format!("{}%", b)).unwrap_or_else(|| "?".to_string())
+        }
+    };
+
+    let device_name = tray.device_name.clone().unwrap_or_else(|| "AirPods".to_string());
+    let left_battery = battery_str(tray.battery_l, tray.battery_l_status);
+    let right_battery = battery_str(tray.battery_r, tray.battery_r_status);
+    let case_battery = battery_str(tray.battery_c, tray.battery_c_status);
+    let anc_mode = tray.listening_mode
+        .map(|b| AirPodsNoiseControlMode::from_byte(b).to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let charging = matches!(tray.battery_l_status, Some(BatteryStatus::Charging))
+        || matches!(tray.battery_r_status, Some(BatteryStatus::Charging));
+
+    let out = template
+        .replace("{device_name}", &device_name)
+        .replace("{left_battery}", &left_battery)
+        .replace("{right_battery}", &right_battery)
+        .replace("{case_battery}", &case_battery)
+        .replace("{anc_mode}", &anc_mode);
+
+    expand_conditional(&out, "charging", charging)
+}
+
+/// Expands a `{name?text}` conditional placeholder: `text` is kept verbatim
+/// when `value` is true, and the whole placeholder is dropped otherwise.
+fn expand_conditional(input: &str, name: &str, value: bool) -> String {
+    let marker = format!("{{{}?", name);
+    let Some(start) = input.find(&marker) else {
+        return input.to_string();
+    };
+    let Some(end_rel) = input[start..].find('}') else {
+        return input.to_string();
+    };
+    let end = start + end_rel;
+    let text = &input[start + marker.len()..end];
+    let replacement = if value { text } else { "" };
+    format!("{}{}{}", &input[..start], replacement, &input[end + 1..])
+}
+
+fn generate_icon(text: &str, mode: TrayIconMode, charging: bool, tray: &MyTray) -> Icon {
     use ab_glyph::{FontRef, PxScale};
     use image::{ImageBuffer, Rgba};
     use imageproc::drawing::draw_text_mut;
@@ -201,7 +393,9 @@ fn generate_icon(text: &str, text_mode: bool, charging: bool) -> Icon {
             };
         }
     };
-    if !text_mode {
+    if mode == TrayIconMode::Segments {
+        draw_segments(&mut img, width, height, tray);
+    } else if mode == TrayIconMode::Ring {
         let percentage = text.parse::<f32>().unwrap_or(0.0) / 100.0;
 
         let center_x = width as f32 / 2.0;
@@ -284,4 +478,52 @@ fn generate_icon(text: &str, text_mode: bool, charging: bool) -> Icon {
         height: height as i32,
         data,
     }
+}
+
+/// Draws three vertical regions (L | R | C), each a filled bar whose height
+/// is proportional to that component's battery percentage. Disconnected
+/// components are left blank so the user can tell at a glance which pieces
+/// aren't present.
+fn draw_segments(img: &mut image::ImageBuffer<image::Rgba<u8>, Vec<u8>>, width: u32, height: u32, tray: &MyTray) {
+    use image::Rgba;
+
+    let region_width = width / 3;
+    let margin = 4;
+    let bar_width = region_width.saturating_sub(margin * 2);
+    let max_bar_height = height - margin * 2;
+
+    let components: [(Option<u8>, Option<BatteryStatus>); 3] = [
+        (tray.battery_l, tray.battery_l_status),
+        (tray.battery_r, tray.battery_r_status),
+        (tray.battery_c, tray.battery_c_status),
+    ];
+
+    for (i, (level, status)) in components.iter().enumerate() {
+        if *status == Some(BatteryStatus::Disconnected) || level.is_none() {
+            continue;
+        }
+        let level = level.unwrap();
+        let region_x = i as u32 * region_width;
+        let bar_x = region_x + margin;
+        let bar_height = ((level.min(100) as f32 / 100.0) * max_bar_height as f32).round() as u32;
+        let bar_top = height - margin - bar_height;
+
+        let color = if *status == Some(BatteryStatus::Charging) {
+            Rgba([0u8, 255u8, 0u8, 255u8])
+        } else {
+            Rgba([255u8, 255u8, 255u8, 255u8])
+        };
+
+        // faint background for the whole region so empty slots are visible
+        for y in (height - margin - max_bar_height)..(height - margin) {
+            for x in bar_x..(bar_x + bar_width) {
+                img.put_pixel(x, y, Rgba([128u8, 128u8, 128u8, 80u8]));
+            }
+        }
+        for y in bar_top..(height - margin) {
+            for x in bar_x..(bar_x + bar_width) {
+                img.put_pixel(x, y, color);
+            }
+        }
+    }
 }
\ No newline at end of file
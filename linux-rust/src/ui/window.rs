@@ -1,25 +1,30 @@
 use std::collections::HashMap;
 use iced::widget::button::Style;
 use iced::widget::{button, column, container, pane_grid, text, Space, combo_box, row, text_input, scrollable, vertical_rule, rule, toggler};
-use iced::{daemon, window, Background, Border, Center, Color, Element, Font, Length, Padding, Size, Subscription, Task, Theme};
+use iced::{daemon, keyboard, window, Background, Border, Center, Color, Element, Font, Length, Padding, Size, Subscription, Task, Theme};
 use std::sync::Arc;
 use bluer::{Address, Session};
 use iced::border::Radius;
 use iced::overlay::menu;
 use iced::widget::rule::FillMode;
 use log::{debug, error};
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
 use tokio::sync::{Mutex, RwLock};
 use crate::bluetooth::aacp::{AACPEvent, ControlCommandIdentifiers, BatteryComponent, BatteryStatus};
-use crate::bluetooth::managers::DeviceManagers;
-use crate::devices::enums::{AirPodsNoiseControlMode, AirPodsState, DeviceData, DeviceState, DeviceType, NothingAncMode, NothingState};
+use crate::bluetooth::managers::{key_for_mac, DeviceManagers};
+use crate::bluetooth::scan::{self, DiscoveredDevice, ScanEvent};
+use crate::devices::enums::{AirPodsNoiseControlMode, AirPodsState, DeviceData, DeviceState, DeviceType, GenericBleState, NothingAncMode, NothingState};
+use crate::settings::{load_settings, save_settings};
 use crate::ui::messages::BluetoothUIMessage;
-use crate::utils::{get_devices_path, get_app_settings_path, MyTheme};
+use crate::ui::tray::TrayIconMode;
+use crate::utils::MyTheme;
 use crate::ui::airpods::airpods_view;
 use crate::ui::nothing::nothing_view;
+use crate::ui::generic_ble::generic_ble_view;
 
 pub fn start_ui(
     ui_rx: UnboundedReceiver<BluetoothUIMessage>,
+    ui_tx: tokio::sync::mpsc::UnboundedSender<BluetoothUIMessage>,
     start_minimized: bool,
     device_managers: Arc<RwLock<HashMap<String, DeviceManagers>>>,
 ) -> iced::Result {
@@ -28,7 +33,7 @@ pub fn start_ui(
         .theme(App::theme)
         .font(include_bytes!("../../assets/font/sf_pro.otf").as_slice())
         .default_font(Font::with_name("SF Pro Text"))
-        .run_with(move || App::new(ui_rx, start_minimized, device_managers))
+        .run_with(move || App::new(ui_rx, ui_tx, start_minimized, device_managers))
 }
 
 pub struct App {
@@ -43,9 +48,44 @@ pub struct App {
     device_states: HashMap<String, DeviceState>,
     device_managers: Arc<RwLock<HashMap<String, DeviceManagers>>>,
     pending_add_device: Option<(String, Address)>,
+    /// Substring typed into the "Pick a paired device to add" filter; narrows
+    /// the list and resets keyboard selection whenever it changes.
+    add_device_filter: String,
+    /// Index into the filtered/sorted add-device candidate list, driven by
+    /// ArrowUp/ArrowDown/Tab while the AddDevice tab is open.
+    add_device_selected_index: Option<usize>,
     device_type_state: combo_box::State<DeviceType>,
     selected_device_type: Option<DeviceType>,
-    tray_text_mode: bool
+    tray_icon_mode_state: combo_box::State<TrayIconMode>,
+    selected_tray_icon_mode: TrayIconMode,
+    tray_text_template: String,
+    mic_level: f32,
+    ui_tx: tokio::sync::mpsc::UnboundedSender<BluetoothUIMessage>,
+    scanning: bool,
+    discovered_devices: HashMap<Address, DiscoveredDevice>,
+    scan_rx: Option<Arc<Mutex<UnboundedReceiver<ScanEvent>>>>,
+    scan_tx: Option<tokio::sync::mpsc::UnboundedSender<ScanEvent>>,
+    scan_task: Option<tokio::task::JoinHandle<()>>,
+    /// Hides discovered devices we couldn't match to a supported
+    /// `DeviceType` -- on by default so the list isn't dominated by unrelated
+    /// BR/EDR/BLE traffic, but toggle-able off since `detect_device_type` is
+    /// a manufacturer-ID guess and can legitimately miss a real device.
+    recognized_devices_only: bool,
+    pending_passkey: Option<Address>,
+    passkey_input: String,
+    reconnecting_macs: std::collections::HashSet<String>,
+    /// Last known desktop color-scheme preference from the settings portal,
+    /// used to resolve `MyTheme::System`. `None` until the initial portal
+    /// read completes (or fails, e.g. no portal running), in which case
+    /// `theme()` falls back to `MyTheme::Dark`.
+    system_prefers_dark: Option<bool>,
+    system_theme_rx: Arc<Mutex<UnboundedReceiver<bool>>>,
+    /// MACs from devices.json that haven't reported their initial
+    /// `DeviceConnected` yet this run. Acts as a startup barrier: the pane of
+    /// a device still in this set shows a "connecting" placeholder instead of
+    /// rendering (likely stale/default) state from a monitor that hasn't
+    /// actually established its connection yet.
+    startup_devices_pending: std::collections::HashSet<String>,
 }
 
 pub struct BluetoothState {
@@ -76,7 +116,27 @@ pub enum Message {
     ConfirmAddDevice,
     CancelAddDevice,
     StateChanged(String, DeviceState),
-    TrayTextModeChanged(bool) // yes, I know I should add all settings to a struct, but I'm lazy
+    TrayIconModeSelected(TrayIconMode),
+    MicLevelTick,
+    SetNoiseControl(String, NothingAncMode),
+    NoiseControlModeApplied(String, NothingAncMode, bool),
+    TrayTextTemplateChanged(String),
+    StatusExportDbusToggled(bool),
+    StartScan,
+    StopScan,
+    ScanEventReceived(ScanEvent),
+    PairDevice(Address),
+    RecognizedDevicesOnlyToggled(bool),
+    PasskeyInputChanged(String),
+    SubmitPasskey,
+    CancelPasskeyDialog,
+    RemoveDevice(String),
+    AddDeviceFilterChanged(String),
+    AddDeviceSelectNext,
+    AddDeviceSelectPrev,
+    AddDeviceConfirmSelected,
+    SystemThemeChanged(bool),
+    StartupBarrierTimeout,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -95,6 +155,7 @@ pub enum Pane {
 impl App {
     pub fn new(
         ui_rx: UnboundedReceiver<BluetoothUIMessage>,
+        ui_tx: tokio::sync::mpsc::UnboundedSender<BluetoothUIMessage>,
         start_minimized: bool,
         device_managers: Arc<RwLock<HashMap<String, DeviceManagers>>>,
     ) -> (Self, Task<Message>) {
@@ -109,6 +170,23 @@ impl App {
             |msg| msg,
         );
 
+        let (system_theme_tx, system_theme_rx) = unbounded_channel::<bool>();
+        crate::ui::system_theme::spawn_listener(system_theme_tx);
+        let system_theme_rx = Arc::new(Mutex::new(system_theme_rx));
+        let system_theme_wait_task = Task::perform(
+            wait_for_system_theme_change(Arc::clone(&system_theme_rx)),
+            |msg| msg,
+        );
+        let system_theme_read_task = Task::perform(read_initial_system_theme(), |msg| msg);
+
+        // Don't let a device that never reconnects (out of range at launch,
+        // powered off, etc.) block the startup barrier forever.
+        const STARTUP_BARRIER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+        let startup_barrier_timeout_task = Task::perform(
+            tokio::time::sleep(STARTUP_BARRIER_TIMEOUT),
+            |_| Message::StartupBarrierTimeout,
+        );
+
         let (window, open_task) = if start_minimized {
             (None, Task::none())
         } else {
@@ -119,18 +197,12 @@ impl App {
             (Some(id), open.map(Message::WindowOpened))
         };
 
-        let app_settings_path = get_app_settings_path();
-        let settings = std::fs::read_to_string(&app_settings_path)
-            .ok()
-            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
-        let selected_theme = settings.clone()
-            .and_then(|v| v.get("theme").cloned())
-            .and_then(|t| serde_json::from_value(t).ok())
-            .unwrap_or(MyTheme::Dark);
-        let tray_text_mode = settings.clone()
-            .and_then(|v| v.get("tray_text_mode").cloned())
-            .and_then(|ttm| serde_json::from_value(ttm).ok())
-            .unwrap_or(false);
+        let settings = load_settings();
+        let selected_theme = settings.theme;
+        let selected_tray_icon_mode = settings.tray_icon_mode;
+        let tray_text_template = settings.tray_text_template;
+
+        crate::status_export::start();
 
         let bluetooth_state = BluetoothState::new();
 
@@ -149,6 +221,7 @@ impl App {
                 panes,
                 selected_tab: Tab::Device("none".to_string()),
                 theme_state: combo_box::State::new(vec![
+                    MyTheme::System,
                     MyTheme::Light,
                     MyTheme::Dark,
                     MyTheme::Dracula,
@@ -178,14 +251,37 @@ impl App {
                 paired_devices: HashMap::new(),
                 device_states,
                 pending_add_device: None,
+                add_device_filter: String::new(),
+                add_device_selected_index: None,
                 device_type_state: combo_box::State::new(vec![
-                    DeviceType::Nothing
+                    DeviceType::Nothing,
+                    DeviceType::GenericBle,
                 ]),
                 selected_device_type: None,
                 device_managers,
-                tray_text_mode
+                tray_icon_mode_state: combo_box::State::new(vec![
+                    TrayIconMode::Ring,
+                    TrayIconMode::Text,
+                    TrayIconMode::Segments,
+                ]),
+                selected_tray_icon_mode,
+                tray_text_template,
+                mic_level: 0.0,
+                ui_tx,
+                scanning: false,
+                discovered_devices: HashMap::new(),
+                scan_rx: None,
+                scan_tx: None,
+                scan_task: None,
+                recognized_devices_only: true,
+                pending_passkey: None,
+                passkey_input: String::new(),
+                reconnecting_macs: std::collections::HashSet::new(),
+                system_prefers_dark: None,
+                system_theme_rx,
+                startup_devices_pending: crate::devices::config::load().iter().map(|(mac, _, _)| mac.to_string()).collect(),
             },
-            Task::batch(vec![open_task, wait_task])
+            Task::batch(vec![open_task, wait_task, system_theme_wait_task, system_theme_read_task, startup_barrier_timeout_task])
         )
     }
 
@@ -193,6 +289,26 @@ impl App {
         "LibrePods".to_string()
     }
 
+    /// Paired-but-not-yet-added devices, narrowed by `add_device_filter`
+    /// (matched against name or address) and sorted by name so keyboard
+    /// navigation has a stable order to walk over -- mirrors what the
+    /// AddDevice tab's "Pick a paired device to add" list renders.
+    fn filtered_add_candidates(&self) -> Vec<(String, Address)> {
+        let devices_list = crate::devices::config::load().flatten();
+        let filter = self.add_device_filter.to_lowercase();
+        let mut candidates: Vec<(String, Address)> = self.paired_devices.iter()
+            .filter(|(_, addr)| !devices_list.contains_key(&addr.to_string()))
+            .filter(|(name, addr)| {
+                filter.is_empty()
+                    || name.to_lowercase().contains(&filter)
+                    || addr.to_string().to_lowercase().contains(&filter)
+            })
+            .map(|(name, addr)| (name.clone(), *addr))
+            .collect();
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+        candidates
+    }
+
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::WindowOpened(id) => {
@@ -215,10 +331,28 @@ impl App {
             }
             Message::ThemeSelected(theme) => {
                 self.selected_theme = theme;
-                let app_settings_path = get_app_settings_path();
-                let settings = serde_json::json!({"theme": self.selected_theme, "tray_text_mode": self.tray_text_mode});
-                debug!("Writing settings to {}: {}", app_settings_path.to_str().unwrap() , settings);
-                std::fs::write(app_settings_path, settings.to_string()).ok();
+                let mut settings = load_settings();
+                settings.theme = theme;
+                save_settings(settings);
+                Task::none()
+            }
+            Message::SystemThemeChanged(prefers_dark) => {
+                self.system_prefers_dark = Some(prefers_dark);
+                let system_theme_rx = Arc::clone(&self.system_theme_rx);
+                Task::perform(
+                    wait_for_system_theme_change(system_theme_rx),
+                    |msg| msg,
+                )
+            }
+            Message::StartupBarrierTimeout => {
+                if !self.startup_devices_pending.is_empty() {
+                    debug!(
+                        "Startup barrier timed out with {} device(s) still not connected: {:?}",
+                        self.startup_devices_pending.len(),
+                        self.startup_devices_pending,
+                    );
+                    self.startup_devices_pending.clear();
+                }
                 Task::none()
             }
             Message::CopyToClipboard(data) => {
@@ -265,6 +399,7 @@ impl App {
                             |msg| msg,
                         );
                         debug!("Device connected: {}. Adding to connected devices list", mac);
+                        self.startup_devices_pending.remove(&mac);
                         let mut already_connected = false;
                         for device in &self.bluetooth_state.connected_devices {
                             if device == &mac {
@@ -280,36 +415,19 @@ impl App {
                         //     conversation_awareness_enabled: false,
                         // }));
 
-                        let type_ = {
-                            let devices_json = std::fs::read_to_string(get_devices_path()).unwrap_or_else(|e| {
-                                error!("Failed to read devices file: {}", e);
-                                "{}".to_string()
-                            });
-                            let devices_list: HashMap<String, DeviceData> = serde_json::from_str(&devices_json).unwrap_or_else(|e| {
-                                error!("Deserialization failed: {}", e);
-                                HashMap::new()
-                            });
-                            devices_list.get(&mac).map(|d| d.type_.clone())
-                        };
+                        let type_ = crate::devices::config::load().get(&mac).map(|entry| entry.data.type_.clone());
                         match type_ {
                             Some(DeviceType::AirPods) => {
                                 let device_managers = self.device_managers.blocking_read();
-                                let device_manager = device_managers.get(&mac).unwrap();
+                                let key = key_for_mac(&device_managers, &mac).expect("device_managers entry for just-connected device");
+                                let device_manager = device_managers.get(key).unwrap();
                                 let aacp_manager = device_manager.get_aacp().unwrap();
                                 let aacp_manager_state = aacp_manager.state.clone();
                                 let state = aacp_manager_state.blocking_lock();
                                 debug!("AACP manager found for AirPods device {}", mac);
-                                let device_name = {
-                                    let devices_json = std::fs::read_to_string(get_devices_path()).unwrap_or_else(|e| {
-                                        error!("Failed to read devices file: {}", e);
-                                        "{}".to_string()
-                                    });
-                                    let devices_list: HashMap<String, DeviceData> = serde_json::from_str(&devices_json).unwrap_or_else(|e| {
-                                        error!("Deserialization failed: {}", e);
-                                        HashMap::new()
-                                    });
-                                    devices_list.get(&mac).map(|d| d.name.clone()).unwrap_or_else(|| "Unknown Device".to_string())
-                                };
+                                let device_name = crate::devices::config::load().get(&mac)
+                                    .map(|entry| entry.display_name().to_string())
+                                    .unwrap_or_else(|| "Unknown Device".to_string());
                                 self.device_states.insert(mac.clone(), DeviceState::AirPods(AirPodsState {
                                     device_name,
                                     battery: state.battery_info.clone(),
@@ -348,6 +466,7 @@ impl App {
                                         status.identifier == ControlCommandIdentifiers::AllowOffOption &&
                                         matches!(status.value.as_slice(), [0x01])
                                     }),
+                                    ear_detection_auto_pause_enabled: load_settings().ear_detection_auto_pause_enabled,
                                 }));
                             }
                             Some(DeviceType::Nothing) => {
@@ -366,6 +485,7 @@ impl App {
                             _ => {}
                         }
 
+                        crate::status_export::publish(&self.device_states);
                         Task::batch(vec![
                             wait_task,
                         ])
@@ -379,6 +499,9 @@ impl App {
                         debug!("Device disconnected: {}", mac);
 
                         self.device_states.remove(&mac);
+                        crate::status_export::publish(&self.device_states);
+                        tokio::spawn(crate::audio::on_device_disconnected());
+                        crate::bluetooth::reconnect::spawn_auto_reconnect(mac, self.ui_tx.clone());
                         Task::batch(vec![
                             wait_task,
                         ])
@@ -464,6 +587,64 @@ impl App {
                             }
                             _ => {}
                         }
+                        crate::status_export::publish(&self.device_states);
+                        Task::batch(vec![
+                            wait_task,
+                        ])
+                    }
+                    BluetoothUIMessage::SetConnected(mac, should_be_connected) => {
+                        debug!("Setting connected state for {} to {}", mac, should_be_connected);
+                        let mac_clone = mac.clone();
+                        Task::perform(set_device_connected(mac_clone, should_be_connected), move |_| {
+                            Message::BluetoothMessage(BluetoothUIMessage::NoOp)
+                        })
+                    }
+                    BluetoothUIMessage::SetAutoReconnect(mac, enabled) => {
+                        debug!("Setting auto-reconnect for {} to {}", mac, enabled);
+                        let mut config = crate::devices::config::load();
+                        if let Some(entry) = config.get_mut(&mac) {
+                            entry.data.auto_reconnect = enabled;
+                            crate::devices::config::save(&config);
+                        }
+                        Task::none()
+                    }
+                    BluetoothUIMessage::ReconnectionStatusChanged(mac, is_reconnecting) => {
+                        let ui_rx = Arc::clone(&self.ui_rx);
+                        let wait_task = Task::perform(
+                            wait_for_message(ui_rx),
+                            |msg| msg,
+                        );
+                        debug!("Reconnection status for {}: {}", mac, is_reconnecting);
+                        if is_reconnecting {
+                            self.reconnecting_macs.insert(mac);
+                        } else {
+                            self.reconnecting_macs.remove(&mac);
+                        }
+                        Task::batch(vec![
+                            wait_task,
+                        ])
+                    }
+                    BluetoothUIMessage::BatteryLevelChanged(mac, battery) => {
+                        let ui_rx = Arc::clone(&self.ui_rx);
+                        let wait_task = Task::perform(
+                            wait_for_message(ui_rx),
+                            |msg| msg,
+                        );
+                        debug!("Battery level changed for {}: {:?}", mac, battery);
+                        self.device_states.insert(mac, DeviceState::GenericBle(GenericBleState { battery }));
+                        crate::status_export::publish(&self.device_states);
+                        Task::batch(vec![
+                            wait_task,
+                        ])
+                    }
+                    BluetoothUIMessage::EarDetectionChanged(mac, left_in_ear, right_in_ear) => {
+                        debug!("Ear detection changed for {}: left_in_ear={}, right_in_ear={}", mac, left_in_ear, right_in_ear);
+
+                        let ui_rx = Arc::clone(&self.ui_rx);
+                        let wait_task = Task::perform(
+                            wait_for_message(ui_rx),
+                            |msg| msg,
+                        );
                         Task::batch(vec![
                             wait_task,
                         ])
@@ -471,7 +652,20 @@ impl App {
                     BluetoothUIMessage::ATTNotification(mac, handle, value) => {
                         debug!("ATT Notification for {}: handle=0x{:04X}, value={:?}", mac, handle, value);
 
-                        // TODO: Handle Nothing's ANC Mode changes here
+                        if handle == NOTHING_ANC_CHAR_HANDLE {
+                            match parse_nothing_anc_status(&value) {
+                                Some(mode) => {
+                                    if let Some(DeviceState::Nothing(state)) = self.device_states.get_mut(&mac) {
+                                        debug!("Nothing ANC mode for {} changed to {:?}", mac, mode);
+                                        state.anc_mode = mode;
+                                        crate::status_export::publish(&self.device_states);
+                                    }
+                                }
+                                None => {
+                                    error!("Failed to parse Nothing ANC status notification: {:?}", value);
+                                }
+                            }
+                        }
 
                         let ui_rx = Arc::clone(&self.ui_rx);
                         let wait_task = Task::perform(
@@ -494,8 +688,8 @@ impl App {
                 Task::none()
             }
             Message::StartAddDevice(name, addr) => {
+                self.selected_device_type = self.discovered_devices.get(&addr).and_then(|d| d.detected_type.clone());
                 self.pending_add_device = Some((name, addr));
-                self.selected_device_type = None;
                 Task::none()
             }
             Message::SelectDeviceType(device_type) => {
@@ -505,27 +699,14 @@ impl App {
             Message::ConfirmAddDevice => {
                 if let Some((name, addr)) = self.pending_add_device.take() {
                     if let Some(type_) = self.selected_device_type.take() {
-                        let devices_path = get_devices_path();
-                        let devices_json = std::fs::read_to_string(&devices_path).unwrap_or_else(|e| {
-                            error!("Failed to read devices file: {}", e);
-                            "{}".to_string()
-                        });
-                        let mut devices_list: HashMap<String, DeviceData> = serde_json::from_str(&devices_json).unwrap_or_else(|e| {
-                            error!("Deserialization failed: {}", e);
-                            HashMap::new()
-                        });
-                        devices_list.insert(addr.to_string(), DeviceData {
+                        let mut config = crate::devices::config::load();
+                        config.insert(crate::devices::config::DEFAULT_GROUP, addr.to_string(), crate::devices::config::DeviceEntry::new(DeviceData {
                             name,
                             type_: type_.clone(),
-                            information: None
-                        });
-                        let updated_json = serde_json::to_string(&devices_list).unwrap_or_else(|e| {
-                            error!("Serialization failed: {}", e);
-                            "{}".to_string()
-                        });
-                        if let Err(e) = std::fs::write(&devices_path, updated_json) {
-                            error!("Failed to write devices file: {}", e);
-                        }
+                            information: None,
+                            auto_reconnect: false,
+                        }));
+                        crate::devices::config::save(&config);
                         self.selected_tab = Tab::Device(addr.to_string());
                     }
                 }
@@ -536,20 +717,69 @@ impl App {
                 self.selected_device_type = None;
                 Task::none()
             }
+            Message::RemoveDevice(mac) => {
+                let mut config = crate::devices::config::load();
+                config.remove(&mac);
+                crate::devices::config::save(&config);
+
+                self.device_states.remove(&mac);
+                self.reconnecting_macs.remove(&mac);
+                self.bluetooth_state.connected_devices.retain(|m| m != &mac);
+                self.startup_devices_pending.remove(&mac);
+                if self.selected_tab == Tab::Device(mac.clone()) {
+                    self.selected_tab = Tab::Device("none".to_string());
+                }
+
+                // Drop this device's monitor(s) -- the AACP/ATT manager's
+                // notification-listening task exits once its last Arc is
+                // dropped, rather than reaching into its internals here.
+                let device_managers = Arc::clone(&self.device_managers);
+                tokio::spawn(async move {
+                    let mut device_managers = device_managers.write().await;
+                    if let Some(key) = key_for_mac(&device_managers, &mac).cloned() {
+                        device_managers.remove(&key);
+                    }
+                });
+                Task::none()
+            }
+            Message::AddDeviceFilterChanged(filter) => {
+                self.add_device_filter = filter;
+                self.add_device_selected_index = None;
+                Task::none()
+            }
+            Message::AddDeviceSelectNext => {
+                let len = self.filtered_add_candidates().len();
+                if len > 0 {
+                    self.add_device_selected_index = Some(match self.add_device_selected_index {
+                        Some(i) if i + 1 < len => i + 1,
+                        _ => 0,
+                    });
+                }
+                Task::none()
+            }
+            Message::AddDeviceSelectPrev => {
+                let len = self.filtered_add_candidates().len();
+                if len > 0 {
+                    self.add_device_selected_index = Some(match self.add_device_selected_index {
+                        Some(i) if i > 0 => i - 1,
+                        _ => len - 1,
+                    });
+                }
+                Task::none()
+            }
+            Message::AddDeviceConfirmSelected => {
+                let candidate = self.add_device_selected_index
+                    .and_then(|i| self.filtered_add_candidates().get(i).cloned());
+                if let Some((name, addr)) = candidate {
+                    self.update(Message::StartAddDevice(name, addr))
+                } else {
+                    Task::none()
+                }
+            }
             Message::StateChanged(mac, state) => {
                 self.device_states.insert(mac.clone(), state);
                 // if airpods, update the noise control state combo box based on allow off mode
-                let type_ = {
-                    let devices_json = std::fs::read_to_string(get_devices_path()).unwrap_or_else(|e| {
-                        error!("Failed to read devices file: {}", e);
-                        "{}".to_string()
-                    });
-                    let devices_list: HashMap<String, DeviceData> = serde_json::from_str(&devices_json).unwrap_or_else(|e| {
-                        error!("Deserialization failed: {}", e);
-                        HashMap::new()
-                    });
-                    devices_list.get(&mac).map(|d| d.type_.clone())
-                };
+                let type_ = crate::devices::config::load().get(&mac).map(|entry| entry.data.type_.clone());
                 match type_ {
                     Some(DeviceType::AirPods) => {
                         if let Some(DeviceState::AirPods(state)) = self.device_states.get_mut(&mac) {
@@ -570,28 +800,179 @@ impl App {
                     }
                     _ => {}
                 }
+                crate::status_export::publish(&self.device_states);
+                Task::none()
+            }
+            Message::TrayIconModeSelected(mode) => {
+                self.selected_tray_icon_mode = mode;
+                let mut settings = load_settings();
+                settings.tray_icon_mode = mode;
+                save_settings(settings);
+                Task::none()
+            }
+            Message::MicLevelTick => {
+                self.mic_level = crate::mic_meter::current_level();
+                Task::none()
+            }
+            Message::SetNoiseControl(mac, mode) => {
+                let device_managers = self.device_managers.blocking_read();
+                let att_manager = key_for_mac(&device_managers, &mac)
+                    .and_then(|key| device_managers.get(key))
+                    .and_then(|managers| managers.get_att());
+                match att_manager {
+                    Some(att_manager) => {
+                        debug!("Requesting noise control mode {:?} for {}", mode, mac);
+                        let byte = mode.to_byte();
+                        Task::perform(
+                            async move { att_manager.set_noise_control_mode(byte).await },
+                            move |result| Message::NoiseControlModeApplied(mac, mode, result.is_ok()),
+                        )
+                    }
+                    None => {
+                        error!("No ATT manager found for Nothing device {}", mac);
+                        Task::none()
+                    }
+                }
+            }
+            Message::NoiseControlModeApplied(mac, mode, succeeded) => {
+                if succeeded {
+                    debug!("Noise control mode for {} set to {:?}", mac, mode);
+                    if let Some(DeviceState::Nothing(state)) = self.device_states.get_mut(&mac) {
+                        state.anc_mode = mode;
+                    }
+                    crate::status_export::publish(&self.device_states);
+                } else {
+                    error!("Failed to set noise control mode {:?} for {}", mode, mac);
+                }
+                Task::none()
+            }
+            Message::TrayTextTemplateChanged(template) => {
+                self.tray_text_template = template.clone();
+                let mut settings = load_settings();
+                settings.tray_text_template = template;
+                save_settings(settings);
+                Task::none()
+            }
+            Message::StatusExportDbusToggled(is_enabled) => {
+                let mut settings = load_settings();
+                settings.status_export_dbus = is_enabled;
+                save_settings(settings);
+                if is_enabled {
+                    crate::status_export::start_dbus_service();
+                }
+                Task::none()
+            }
+            Message::StartScan => {
+                if self.scanning {
+                    return Task::none();
+                }
+                self.scanning = true;
+                self.discovered_devices.clear();
+                let (scan_tx, scan_rx) = unbounded_channel::<ScanEvent>();
+                let scan_rx = Arc::new(Mutex::new(scan_rx));
+                self.scan_rx = Some(Arc::clone(&scan_rx));
+                self.scan_tx = Some(scan_tx.clone());
+                self.scan_task = Some(tokio::spawn(async move {
+                    match Session::new().await {
+                        Ok(session) => match session.default_adapter().await {
+                            Ok(adapter) => {
+                                if let Err(e) = scan::run_discovery(adapter, scan_tx).await {
+                                    error!("Discovery failed: {}", e);
+                                }
+                            }
+                            Err(e) => error!("Failed to get default adapter for discovery: {}", e),
+                        },
+                        Err(e) => error!("Failed to start bluer session for discovery: {}", e),
+                    }
+                }));
+                Task::perform(wait_for_scan_event(scan_rx), |msg| msg)
+            }
+            Message::StopScan => {
+                self.scanning = false;
+                if let Some(task) = self.scan_task.take() {
+                    task.abort();
+                }
+                self.scan_rx = None;
+                self.scan_tx = None;
+                Task::none()
+            }
+            Message::ScanEventReceived(event) => {
+                match event {
+                    ScanEvent::DeviceUpdated(device) => {
+                        self.discovered_devices.insert(device.address, device);
+                    }
+                    ScanEvent::DeviceRemoved(addr) => {
+                        self.discovered_devices.remove(&addr);
+                    }
+                    ScanEvent::PasskeyRequested { address } => {
+                        debug!("Pairing agent requested a passkey for {}", address);
+                        self.pending_passkey = Some(address);
+                        self.passkey_input.clear();
+                    }
+                    ScanEvent::PairingSucceeded(addr) => {
+                        debug!("Paired with {}", addr);
+                        self.pending_passkey = None;
+                        if let Some(device) = self.discovered_devices.get_mut(&addr) {
+                            device.bonded = true;
+                        }
+                    }
+                    ScanEvent::PairingFailed(addr, reason) => {
+                        error!("Pairing with {} failed: {}", addr, reason);
+                        self.pending_passkey = None;
+                    }
+                }
+                match &self.scan_rx {
+                    Some(scan_rx) => Task::perform(wait_for_scan_event(Arc::clone(scan_rx)), |msg| msg),
+                    None => Task::none(),
+                }
+            }
+            Message::PairDevice(addr) => {
+                if let Some(scan_tx) = self.scan_tx.clone() {
+                    Task::perform(
+                        async move {
+                            let Ok(session) = Session::new().await else {
+                                error!("Failed to start bluer session to pair {}", addr);
+                                return;
+                            };
+                            let Ok(adapter) = session.default_adapter().await else {
+                                error!("Failed to get default adapter to pair {}", addr);
+                                return;
+                            };
+                            scan::pair_device(session, adapter, addr, scan_tx).await;
+                        },
+                        |_| Message::BluetoothMessage(BluetoothUIMessage::NoOp),
+                    )
+                } else {
+                    Task::none()
+                }
+            }
+            Message::RecognizedDevicesOnlyToggled(enabled) => {
+                self.recognized_devices_only = enabled;
+                Task::none()
+            }
+            Message::PasskeyInputChanged(input) => {
+                self.passkey_input = input;
                 Task::none()
             }
-            Message::TrayTextModeChanged(is_enabled) => {
-                self.tray_text_mode = is_enabled;
-                let app_settings_path = get_app_settings_path();
-                let settings = serde_json::json!({"theme": self.selected_theme, "tray_text_mode": self.tray_text_mode});
-                debug!("Writing settings to {}: {}", app_settings_path.to_str().unwrap() , settings);
-                std::fs::write(app_settings_path, settings.to_string()).ok();
+            Message::SubmitPasskey => {
+                if let (Some(_), Ok(passkey)) = (&self.pending_passkey, self.passkey_input.parse::<u32>()) {
+                    self.pending_passkey = None;
+                    Task::perform(scan::submit_passkey(passkey), |_| Message::BluetoothMessage(BluetoothUIMessage::NoOp))
+                } else {
+                    Task::none()
+                }
+            }
+            Message::CancelPasskeyDialog => {
+                self.pending_passkey = None;
+                self.passkey_input.clear();
                 Task::none()
             }
         }
     }
 
     fn view(&self, _id: window::Id) -> Element<'_, Message> {
-        let devices_json = std::fs::read_to_string(get_devices_path()).unwrap_or_else(|e| {
-            error!("Failed to read devices file: {}", e);
-            "{}".to_string()
-        });
-        let devices_list: HashMap<String, DeviceData> = serde_json::from_str(&devices_json).unwrap_or_else(|e| {
-            error!("Deserialization failed: {}", e);
-            HashMap::new()
-        });
+        let device_config = crate::devices::config::load();
+        let devices_list: HashMap<String, DeviceData> = device_config.flatten();
         let pane_grid = pane_grid::PaneGrid::new(&self.panes, |_pane_id, pane, _is_maximized| {
             match pane {
                 Pane::Sidebar => {
@@ -639,8 +1020,16 @@ impl App {
                                                 )
                                             }
                                         }
+                                        Some(DeviceState::GenericBle(state)) => {
+                                            match state.battery.first().map(|x| x.level) {
+                                                Some(level) => format!("􀺹 {}%", level),
+                                                None => "Connected".to_string(),
+                                            }
+                                        }
                                         _ => "Connected".to_string(),
                                     }
+                                } else if self.reconnecting_macs.contains(mac_addr) {
+                                    "Reconnecting…".to_string()
                                 } else {
                                     mac_addr.to_string()
                                 }
@@ -707,17 +1096,29 @@ impl App {
                     };
 
                     let mut devices = column!().spacing(4);
-                    let mut devices_vec: Vec<(String, DeviceData)> = devices_list.clone().into_iter().collect();
-                    devices_vec.sort_by(|a, b| a.1.name.cmp(&b.1.name));
-                    for (mac, device) in devices_vec {
-                        let name = device.name.clone();
-                        let tab_button = create_tab_button(
-                            Tab::Device(mac.clone()),
-                            &name,
-                            &mac,
-                            self.bluetooth_state.connected_devices.contains(&mac)
+                    for group in &device_config.groups {
+                        if group.devices.is_empty() {
+                            continue;
+                        }
+                        let mut group_devices: Vec<(&String, &crate::devices::config::DeviceEntry)> = group.devices.iter().collect();
+                        group_devices.sort_by(|a, b| a.1.display_name().cmp(b.1.display_name()));
+
+                        devices = devices.push(
+                            text(group.name.clone()).size(12).style(|theme: &Theme| {
+                                let mut style = text::Style::default();
+                                style.color = Some(theme.palette().text.scale_alpha(0.6));
+                                style
+                            })
                         );
-                        devices = devices.push(tab_button);
+                        for (mac, entry) in group_devices {
+                            let tab_button = create_tab_button(
+                                Tab::Device(mac.clone()),
+                                entry.display_name(),
+                                mac,
+                                self.bluetooth_state.connected_devices.contains(mac)
+                            );
+                            devices = devices.push(tab_button);
+                        }
                     }
 
                     let settings = create_settings_button();
@@ -782,27 +1183,34 @@ impl App {
                                 )
                                     .center_x(Length::Fill)
                                     .center_y(Length::Fill)
+                            } else if self.startup_devices_pending.contains(id) {
+                                container(
+                                    text(format!(
+                                        "Connecting to {} device(s) from last session...",
+                                        self.startup_devices_pending.len()
+                                    )).size(16)
+                                )
+                                    .center_x(Length::Fill)
+                                    .center_y(Length::Fill)
                             } else {
                                 let device_type = devices_list.get(id).map(|d| d.type_.clone());
                                 let device_state = self.device_states.get(id);
                                 debug!("Rendering device view for {}: type={:?}, state={:?}", id, device_type, device_state);
-                                match device_type {
+                                let device_view = match device_type {
                                     Some(DeviceType::AirPods) => {
                                         let view = device_state.as_ref().and_then(|state| {
                                             match state {
                                                 DeviceState::AirPods(state) => {
-                                                    device_managers.get(id).and_then(|managers| {
-                                                        managers.get_aacp().and_then(|aacp_manager| {
-                                                            // managers.get_att().map(|att_manager| {
-                                                                Some(airpods_view(
-                                                                    id,
-                                                                    &devices_list,
-                                                                    state,
-                                                                    aacp_manager.clone()
-                                                                ),
-                                                                    // att_manager.clone(),
-                                                                )
-                                                            // })
+                                                    key_for_mac(&device_managers, id).and_then(|key| device_managers.get(key)).and_then(|managers| {
+                                                        managers.get_aacp().map(|_aacp_manager| {
+                                                            airpods_view(
+                                                                id,
+                                                                &devices_list,
+                                                                state,
+                                                                self.bluetooth_state.connected_devices.contains(id),
+                                                                self.mic_level,
+                                                                load_settings().mic_sensitivity_threshold,
+                                                            )
                                                         })
                                                     })
                                                 }
@@ -819,9 +1227,9 @@ impl App {
                                     }
                                     Some(DeviceType::Nothing) => {
                                         if let Some(DeviceState::Nothing(state)) = device_state {
-                                            if let Some(device_managers) = device_managers.get(id) {
-                                                if let Some(att_manager) = device_managers.get_att() {
-                                                    nothing_view(id, &devices_list, state, att_manager.clone())
+                                            if let Some(device_managers) = key_for_mac(&device_managers, id).and_then(|key| device_managers.get(key)) {
+                                                if device_managers.get_att().is_some() {
+                                                    nothing_view(id, &devices_list, state)
                                                 } else {
                                                     error!("No ATT manager found for Nothing device {}", id);
                                                     container(
@@ -846,20 +1254,58 @@ impl App {
                                                 .center_y(Length::Fill)
                                         }
                                     }
+                                    Some(DeviceType::GenericBle) => {
+                                        if let Some(DeviceState::GenericBle(state)) = device_state {
+                                            generic_ble_view(id, state)
+                                        } else {
+                                            container(
+                                                text("No state available for this device yet").size(16)
+                                            )
+                                                .center_x(Length::Fill)
+                                                .center_y(Length::Fill)
+                                        }
+                                    }
                                     _ => {
                                         container(text("Unsupported device").size(16))
                                             .center_x(Length::Fill)
                                             .center_y(Length::Fill)
                                     }
-                                }
+                                };
+
+                                let device_name = devices_list.get(id).map(|d| d.name.clone()).unwrap_or_else(|| id.clone());
+                                container(
+                                    column![
+                                        row![
+                                            text(device_name).size(18),
+                                            Space::with_width(Length::Fill),
+                                            button(text("Remove Device").size(14))
+                                                .style(|theme: &Theme, _status| {
+                                                    let mut style = Style::default();
+                                                    style.text_color = theme.palette().danger;
+                                                    style.background = Some(Background::Color(theme.palette().danger.scale_alpha(0.1)));
+                                                    style.border = Border {
+                                                        width: 1.0,
+                                                        color: theme.palette().danger.scale_alpha(0.3),
+                                                        radius: Radius::from(8.0),
+                                                    };
+                                                    style
+                                                })
+                                                .padding(6)
+                                                .on_press(Message::RemoveDevice(id.clone()))
+                                        ]
+                                            .align_y(Center)
+                                            .padding(8),
+                                        device_view
+                                    ]
+                                )
                             }
                         }
                         Tab::Settings => {
                             let tray_text_mode_toggle = container(
                                 row![
                                     column![
-                                        text("Use text in tray").size(16),
-                                        text("Use text for battery status in tray instead of a progress bar.").size(12).style(
+                                        text("Tray icon style").size(16),
+                                        text("Ring: progress ring. Text: battery percentage. Segments: a small bar per component.").size(12).style(
                                             |theme: &Theme| {
                                                 let mut style = text::Style::default();
                                                 style.color = Some(theme.palette().text.scale_alpha(0.7));
@@ -867,12 +1313,50 @@ impl App {
                                             }
                                         ).width(Length::Fill)
                                     ].width(Length::Fill),
-                                    toggler(self.tray_text_mode)
-                                        .on_toggle(move |is_enabled| {
-                                            Message::TrayTextModeChanged(is_enabled)
-                                        })
-                                    .spacing(0)
-                                    .size(20)
+                                    combo_box(
+                                        &self.tray_icon_mode_state,
+                                        "Select tray icon style",
+                                        Some(&self.selected_tray_icon_mode),
+                                        Message::TrayIconModeSelected
+                                    )
+                                    .input_style(
+                                        |theme: &Theme, _status| {
+                                            text_input::Style {
+                                                background: Background::Color(theme.palette().primary.scale_alpha(0.2)),
+                                                border: Border {
+                                                    width: 1.0,
+                                                    color: theme.palette().text.scale_alpha(0.3),
+                                                    radius: Radius::from(4.0)
+                                                },
+                                                icon: Default::default(),
+                                                placeholder: theme.palette().text,
+                                                value: theme.palette().text,
+                                                selection: Default::default(),
+                                            }
+                                        }
+                                    )
+                                    .menu_style(
+                                        |theme: &Theme| {
+                                            menu::Style {
+                                                background: Background::Color(theme.palette().background),
+                                                border: Border {
+                                                    width: 1.0,
+                                                    color: theme.palette().text,
+                                                    radius: Radius::from(4.0)
+                                                },
+                                                text_color: theme.palette().text,
+                                                selected_text_color: theme.palette().text,
+                                                selected_background: Background::Color(theme.palette().primary.scale_alpha(0.3)),
+                                            }
+                                        }
+                                    )
+                                    .padding(Padding{
+                                        top: 5.0,
+                                        bottom: 5.0,
+                                        left: 10.0,
+                                        right: 10.0,
+                                    })
+                                    .width(Length::from(160))
                                     ]
                                         .align_y(Center)
                                         .spacing(12)
@@ -982,11 +1466,90 @@ impl App {
                                 ]
                                 .spacing(12);
 
+                            let tray_text_template_row = container(
+                                row![
+                                    column![
+                                        text("Tray tooltip template").size(16),
+                                        text("Placeholders: {device_name} {left_battery} {right_battery} {case_battery} {anc_mode} {charging?text}").size(12).style(
+                                            |theme: &Theme| {
+                                                let mut style = text::Style::default();
+                                                style.color = Some(theme.palette().text.scale_alpha(0.7));
+                                                style
+                                            }
+                                        ).width(Length::Fill)
+                                    ].width(Length::Fill),
+                                    text_input("{device_name} {left_battery}/{right_battery}", &self.tray_text_template)
+                                        .on_input(Message::TrayTextTemplateChanged)
+                                        .width(Length::from(260))
+                                    ]
+                                        .align_y(Center)
+                                        .spacing(12)
+                                    )
+                                        .padding(Padding{
+                                            top: 5.0,
+                                            bottom: 5.0,
+                                            left: 18.0,
+                                            right: 18.0,
+                                        })
+                                        .style(
+                                            |theme: &Theme| {
+                                                let mut style = container::Style::default();
+                                                style.background = Some(Background::Color(theme.palette().primary.scale_alpha(0.1)));
+                                                let mut border = Border::default();
+                                                border.color = theme.palette().primary.scale_alpha(0.5);
+                                                style.border = border.rounded(16);
+                                                style
+                                            }
+                                        )
+                                    .align_y(Center);
+
+                            let status_export_dbus_toggle = container(
+                                row![
+                                    column![
+                                        text("Export battery status over D-Bus").size(16),
+                                        text("Publish per-device battery/charging/noise-control state under the librepods bus name, for status bars that subscribe to PropertiesChanged.").size(12).style(
+                                            |theme: &Theme| {
+                                                let mut style = text::Style::default();
+                                                style.color = Some(theme.palette().text.scale_alpha(0.7));
+                                                style
+                                            }
+                                        ).width(Length::Fill)
+                                    ].width(Length::Fill),
+                                    toggler(load_settings().status_export_dbus)
+                                        .on_toggle(Message::StatusExportDbusToggled)
+                                    .spacing(0)
+                                    .size(20)
+                                    ]
+                                        .align_y(Center)
+                                        .spacing(12)
+                                    )
+                                        .padding(Padding{
+                                            top: 5.0,
+                                            bottom: 5.0,
+                                            left: 18.0,
+                                            right: 18.0,
+                                        })
+                                        .style(
+                                            |theme: &Theme| {
+                                                let mut style = container::Style::default();
+                                                style.background = Some(Background::Color(theme.palette().primary.scale_alpha(0.1)));
+                                                let mut border = Border::default();
+                                                border.color = theme.palette().primary.scale_alpha(0.5);
+                                                style.border = border.rounded(16);
+                                                style
+                                            }
+                                        )
+                                    .align_y(Center);
+
                             container(
                                 column![
                                     appearance_settings_col,
                                     Space::with_height(Length::from(20)),
-                                    tray_text_mode_toggle
+                                    tray_text_mode_toggle,
+                                    Space::with_height(Length::from(12)),
+                                    tray_text_template_row,
+                                    Space::with_height(Length::from(12)),
+                                    status_export_dbus_toggle,
                                 ]
                             )
                                 .padding(20)
@@ -996,12 +1559,100 @@ impl App {
                         Tab::AddDevice => {
                             container(
                                 column![
+                                    row![
+                                        text("Discover nearby devices").size(18),
+                                        Space::with_width(Length::Fill),
+                                        text("Recognized only").size(12),
+                                        toggler(self.recognized_devices_only)
+                                            .on_toggle(Message::RecognizedDevicesOnlyToggled)
+                                            .size(18),
+                                        Space::with_width(Length::from(10)),
+                                        button(text(if self.scanning { "Stop Scan" } else { "Start Scan" }).size(14))
+                                            .on_press(if self.scanning { Message::StopScan } else { Message::StartScan })
+                                            .padding(8),
+                                    ]
+                                    .align_y(Center),
+                                    Space::with_height(Length::from(10)),
+                                    {
+                                        let mut discovered_col = column![].spacing(8);
+                                        let mut devices: Vec<&DiscoveredDevice> = self.discovered_devices.values()
+                                            .filter(|d| !self.recognized_devices_only || d.detected_type.is_some())
+                                            .collect();
+                                        devices.sort_by_key(|d| std::cmp::Reverse(d.rssi.unwrap_or(i16::MIN)));
+                                        for device in devices {
+                                            let status = match (device.bonded, device.connected) {
+                                                (_, true) => "Connected",
+                                                (true, false) => "Paired",
+                                                (false, false) => "Not paired",
+                                            };
+                                            let rssi_text = device.rssi.map(|r| format!("{} dBm", r)).unwrap_or_else(|| "?".to_string());
+                                            let mut row_elements = vec![
+                                                column![
+                                                    text(device.name.clone()).size(16),
+                                                    text(format!("{} · {} · {}", device.address, rssi_text, status)).size(12),
+                                                ].into(),
+                                                Space::with_width(Length::Fill).into(),
+                                            ];
+                                            if device.bonded {
+                                                row_elements.push(
+                                                    button(text("Add").size(14))
+                                                        .on_press(Message::StartAddDevice(device.name.clone(), device.address))
+                                                        .padding(8)
+                                                        .into(),
+                                                );
+                                            } else {
+                                                row_elements.push(
+                                                    button(text("Pair").size(14))
+                                                        .on_press(Message::PairDevice(device.address))
+                                                        .padding(8)
+                                                        .into(),
+                                                );
+                                            }
+                                            discovered_col = discovered_col.push(
+                                                container(row(row_elements).align_y(Center))
+                                                    .padding(8)
+                                                    .style(|theme: &Theme| {
+                                                        let mut style = container::Style::default();
+                                                        style.background = Some(Background::Color(theme.palette().primary.scale_alpha(0.1)));
+                                                        let mut border = Border::default();
+                                                        border.color = theme.palette().text;
+                                                        style.border = border.rounded(8);
+                                                        style
+                                                    }),
+                                            );
+                                        }
+                                        if let Some(pending_addr) = self.pending_passkey {
+                                            discovered_col = discovered_col.push(
+                                                container(
+                                                    row![
+                                                        text(format!("Enter passkey for {}:", pending_addr)).size(14),
+                                                        text_input("Passkey", &self.passkey_input)
+                                                            .on_input(Message::PasskeyInputChanged)
+                                                            .width(Length::from(100)),
+                                                        button(text("Confirm").size(14)).on_press(Message::SubmitPasskey).padding(8),
+                                                        button(text("Cancel").size(14)).on_press(Message::CancelPasskeyDialog).padding(8),
+                                                    ]
+                                                    .spacing(8)
+                                                    .align_y(Center),
+                                                )
+                                                .padding(8),
+                                            );
+                                        }
+                                        scrollable(discovered_col).height(Length::from(200)).width(Length::Fill)
+                                    },
+                                    Space::with_height(Length::from(20)),
                                     text("Pick a paired device to add:").size(18),
                                     Space::with_height(Length::from(10)),
+                                    text_input("Filter by name or address", &self.add_device_filter)
+                                        .on_input(Message::AddDeviceFilterChanged)
+                                        .padding(8),
+                                    Space::with_height(Length::from(10)),
                                     {
                                         let mut list_col = column![].spacing(12);
-                                        for device in self.paired_devices.clone() {
-                                            if !devices_list.contains_key(&device.1.to_string()) {
+                                        let candidates = self.filtered_add_candidates();
+                                        for (idx, device) in candidates.iter().enumerate() {
+                                            {
+                                                let is_highlighted = self.add_device_selected_index == Some(idx);
                                                 let mut item_col = column![].spacing(8);
                                                 let mut row_elements = vec![
                                                     column![
@@ -1118,11 +1769,12 @@ impl App {
                                                     container(item_col)
                                                         .padding(8)
                                                         .style(
-                                                            |theme: &Theme| {
+                                                            move |theme: &Theme| {
                                                                 let mut style = container::Style::default();
-                                                                style.background = Some(Background::Color(theme.palette().primary.scale_alpha(0.1)));
+                                                                let alpha = if is_highlighted { 0.3 } else { 0.1 };
+                                                                style.background = Some(Background::Color(theme.palette().primary.scale_alpha(alpha)));
                                                                 let mut border = Border::default();
-                                                                border.color = theme.palette().text;
+                                                                border.color = if is_highlighted { theme.palette().primary } else { theme.palette().text };
                                                                 style.border = border.rounded(8);
                                                                 style
                                                             }
@@ -1130,10 +1782,15 @@ impl App {
                                                 );
                                             }
                                         }
-                                        if self.paired_devices.iter().all(|device| devices_list.contains_key(&device.1.to_string())) && self.pending_add_device.is_none() {
+                                        if candidates.is_empty() && self.pending_add_device.is_none() {
+                                            let message = if self.add_device_filter.is_empty() {
+                                                "No new paired devices found. All paired devices are already added."
+                                            } else {
+                                                "No paired devices match the filter."
+                                            };
                                             list_col = list_col.push(
                                                 container(
-                                                    text("No new paired devices found. All paired devices are already added.").size(16)
+                                                    text(message).size(16)
                                                 )
                                                 .width(Length::Fill)
                                             );
@@ -1162,11 +1819,29 @@ impl App {
     }
 
     fn theme(&self, _id: window::Id) -> Theme {
-        self.selected_theme.into()
+        match self.selected_theme {
+            MyTheme::System if self.system_prefers_dark.unwrap_or(true) => MyTheme::Dark.into(),
+            MyTheme::System => MyTheme::Light.into(),
+            theme => theme.into(),
+        }
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        window::close_events().map(Message::WindowClosed)
+        let mut subs = vec![
+            window::close_events().map(Message::WindowClosed),
+            iced::time::every(std::time::Duration::from_millis(100)).map(|_| Message::MicLevelTick),
+        ];
+        if self.selected_tab == Tab::AddDevice {
+            subs.push(keyboard::on_key_press(|key, _modifiers| match key {
+                keyboard::Key::Named(keyboard::key::Named::ArrowDown) => Some(Message::AddDeviceSelectNext),
+                keyboard::Key::Named(keyboard::key::Named::Tab) => Some(Message::AddDeviceSelectNext),
+                keyboard::Key::Named(keyboard::key::Named::ArrowUp) => Some(Message::AddDeviceSelectPrev),
+                keyboard::Key::Named(keyboard::key::Named::Enter) => Some(Message::AddDeviceConfirmSelected),
+                keyboard::Key::Named(keyboard::key::Named::Escape) => Some(Message::CancelAddDevice),
+                _ => None,
+            }));
+        }
+        Subscription::batch(subs)
     }
 }
 
@@ -1182,6 +1857,66 @@ async fn wait_for_message(
         }
     }
 }
+async fn wait_for_scan_event(scan_rx: Arc<Mutex<UnboundedReceiver<ScanEvent>>>) -> Message {
+    let mut rx = scan_rx.lock().await;
+    match rx.recv().await {
+        Some(event) => Message::ScanEventReceived(event),
+        None => Message::BluetoothMessage(BluetoothUIMessage::NoOp),
+    }
+}
+
+async fn wait_for_system_theme_change(system_theme_rx: Arc<Mutex<UnboundedReceiver<bool>>>) -> Message {
+    let mut rx = system_theme_rx.lock().await;
+    match rx.recv().await {
+        Some(prefers_dark) => Message::SystemThemeChanged(prefers_dark),
+        None => Message::BluetoothMessage(BluetoothUIMessage::NoOp),
+    }
+}
+
+/// One-shot read of the desktop's current color-scheme preference, done on
+/// a blocking task since the portal call goes out over a blocking D-Bus
+/// connection. Only emits `SystemThemeChanged` if the portal answered --
+/// if it's unreachable, `theme()` just falls back to a fixed default.
+async fn read_initial_system_theme() -> Message {
+    match tokio::task::spawn_blocking(crate::ui::system_theme::read_color_scheme_prefers_dark).await {
+        Ok(Some(prefers_dark)) => Message::SystemThemeChanged(prefers_dark),
+        _ => Message::BluetoothMessage(BluetoothUIMessage::NoOp),
+    }
+}
+
+async fn set_device_connected(mac: String, should_be_connected: bool) {
+    let Ok(addr) = mac.parse::<Address>() else {
+        error!("Invalid MAC address for connect/disconnect: {}", mac);
+        return;
+    };
+    let Ok(session) = Session::new().await else {
+        error!("Failed to get bluer session to set connected state for {}", mac);
+        return;
+    };
+    // Resolve against the same --adapter selector main.rs bound to, rather
+    // than BlueZ's "default" adapter, so a device monitored on a
+    // non-default adapter can still be connected/disconnected from the UI.
+    let adapters = match crate::bluetooth::adapters::resolve(&session, crate::bluetooth::adapters::selector()).await {
+        Ok(adapters) => adapters,
+        Err(e) => {
+            error!("Failed to resolve adapter to set connected state for {}: {}", mac, e);
+            return;
+        }
+    };
+    for adapter in adapters {
+        let Ok(device) = adapter.device(addr) else { continue };
+        let result = if should_be_connected {
+            device.connect().await
+        } else {
+            device.disconnect().await
+        };
+        match result {
+            Ok(()) => return,
+            Err(e) => error!("Failed to set connected={} for {} on {}: {}", should_be_connected, mac, adapter.name(), e),
+        }
+    }
+}
+
 async fn load_paired_devices() -> HashMap<String, Address> {
     let mut devices = HashMap::new();
 
@@ -1199,3 +1934,21 @@ async fn load_paired_devices() -> HashMap<String, Address> {
 
     devices
 }
+
+/// Nothing's ANC control characteristic handle. Reverse-engineered from a
+/// handful of captures since Nothing hasn't published a GATT profile; may
+/// need correcting once we see a wider set of devices/firmware.
+const NOTHING_ANC_CHAR_HANDLE: u16 = 0x002f;
+
+/// Nothing's ANC notification frame is a length-prefixed command:
+/// `[length, opcode, anc_status, ...]`. `length` and `opcode` are skipped and
+/// `anc_status` is mapped through `NothingAncMode::from_byte` (the inverse of
+/// `NothingAncMode::to_byte`). Returns `None` for a
+/// payload too short to contain a status byte, or a value we don't recognize.
+fn parse_nothing_anc_status(value: &[u8]) -> Option<NothingAncMode> {
+    let status_byte = *value.get(2)?;
+    match status_byte {
+        0x01 | 0x02 | 0x03 | 0x04 | 0x05 | 0x07 => Some(NothingAncMode::from_byte(status_byte)),
+        _ => None,
+    }
+}